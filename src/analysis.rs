@@ -0,0 +1,309 @@
+//! Graph Analysis
+//!
+//! Analytics over the buyer–producer transaction graph.
+//!
+//! Bipartite projection: buyers only transact with producers, so producers are
+//! linked to each other only through shared buyers. Projecting onto one side gives
+//! a co-purchase graph where:
+//!   P(a, b) = Σ_v min(w(a,v), w(b,v)) over all v on the other side
+//!
+//! (the fee volume both a and b share with common counterparties)
+
+use crate::embeddings;
+use crate::graph;
+
+/// Project the bipartite graph onto the `side` nodes through the `other` nodes
+///
+/// Input:
+/// - weights: bipartite adjacency matrix
+/// - side: nodes to project onto (e.g. producer indices)
+/// - other: nodes on the other side (e.g. buyer indices)
+///
+/// Output: matrix where only side × side entries are non-zero, diagonal is 0
+pub fn project<const N: usize>(
+    weights: &[[f64; N]; N],
+    side: &[usize],
+    other: &[usize],
+) -> [[f64; N]; N] {
+    let mut projected = [[0.0; N]; N];
+
+    // Only pairs that share a counterparty v contribute, so skip zero edges
+    for &v in other {
+        let neighbors: Vec<usize> = side
+            .iter()
+            .copied()
+            .filter(|&a| weights[a][v] > 0.0)
+            .collect();
+        for (i, &a) in neighbors.iter().enumerate() {
+            for &b in &neighbors[i + 1..] {
+                let shared = weights[a][v].min(weights[b][v]);
+                projected[a][b] += shared;
+                projected[b][a] += shared;
+            }
+        }
+    }
+
+    projected
+}
+
+/// Producer–producer co-purchase graph
+pub fn project_producers<const N: usize>(
+    weights: &[[f64; N]; N],
+    producers: &[usize],
+    buyers: &[usize],
+) -> [[f64; N]; N] {
+    project(weights, producers, buyers)
+}
+
+/// Buyer–buyer co-purchase graph
+pub fn project_buyers<const N: usize>(
+    weights: &[[f64; N]; N],
+    producers: &[usize],
+    buyers: &[usize],
+) -> [[f64; N]; N] {
+    project(weights, buyers, producers)
+}
+
+/// Find the k producers most similar to `user` by shared-buyer weight
+///
+/// Similarity is the projected co-purchase weight P(user, p).
+/// Producers with no shared buyers are left out.
+///
+/// Output: up to k (producer, similarity) pairs, most similar first
+pub fn similar_producers<const N: usize>(
+    weights: &[[f64; N]; N],
+    producers: &[usize],
+    buyers: &[usize],
+    user: usize,
+    k: usize,
+) -> Vec<(usize, f64)> {
+    let projected = project_producers(weights, producers, buyers);
+
+    let mut similar: Vec<(usize, f64)> = producers
+        .iter()
+        .filter(|&&p| p != user && projected[user][p] > 0.0)
+        .map(|&p| (p, projected[user][p]))
+        .collect();
+
+    similar.sort_by(|a, b| b.1.total_cmp(&a.1));
+    similar.truncate(k);
+    similar
+}
+
+/// Number of triangles each node participates in
+pub fn triangle_counts<const N: usize>(weights: &[[f64; N]; N]) -> [u64; N] {
+    let mut counts = [0u64; N];
+    for i in 0..N {
+        for j in (i + 1)..N {
+            if weights[i][j] <= 0.0 {
+                continue;
+            }
+            for k in (j + 1)..N {
+                if weights[i][k] > 0.0 && weights[j][k] > 0.0 {
+                    counts[i] += 1;
+                    counts[j] += 1;
+                    counts[k] += 1;
+                }
+            }
+        }
+    }
+    counts
+}
+
+/// Weighted clustering coefficient per node (Onnela et al.)
+///
+/// C_i = 1 / (k_i (k_i - 1)) · Σ_{j≠k} (ŵ_ij · ŵ_ik · ŵ_jk)^(1/3)
+///
+/// Where:
+/// - k_i = number of neighbors of i
+/// - ŵ = w / max(w), weights scaled to 0..1
+///
+/// Dense triangles of heavy edges give values near 1.
+pub fn weighted_clustering<const N: usize>(weights: &[[f64; N]; N]) -> [f64; N] {
+    let max_weight = weights.iter().flatten().cloned().fold(0.0_f64, f64::max);
+    let mut clustering = [0.0; N];
+    if max_weight <= 0.0 {
+        return clustering;
+    }
+
+    for (i, c) in clustering.iter_mut().enumerate() {
+        let neighbors: Vec<usize> = (0..N).filter(|&j| j != i && weights[i][j] > 0.0).collect();
+        let k = neighbors.len();
+        if k < 2 {
+            continue;
+        }
+
+        let mut sum = 0.0;
+        for (a, &j) in neighbors.iter().enumerate() {
+            for &l in &neighbors[a + 1..] {
+                let product = weights[i][j] * weights[i][l] * weights[j][l];
+                sum += 2.0 * (product / max_weight.powi(3)).cbrt();
+            }
+        }
+        *c = sum / (k * (k - 1)) as f64;
+    }
+    clustering
+}
+
+/// Weighted k-core (s-core) decomposition
+///
+/// The s-core is the largest subgraph where every node has strength Σ w(u,v) ≥ s
+/// inside the subgraph. A node's core number is the largest s whose core contains it.
+///
+/// Computed by peeling: repeatedly remove the node with the lowest remaining strength.
+/// Peripheral, weakly connected accounts get low core numbers.
+pub fn core_numbers<const N: usize>(weights: &[[f64; N]; N]) -> [f64; N] {
+    let mut strength: [f64; N] = weights.map(|row| row.iter().sum());
+    let mut removed = [false; N];
+    let mut core = [0.0; N];
+    let mut level = 0.0_f64;
+
+    for _ in 0..N {
+        let u = match (0..N)
+            .filter(|&u| !removed[u])
+            .min_by(|&a, &b| strength[a].total_cmp(&strength[b]))
+        {
+            Some(u) => u,
+            None => break,
+        };
+
+        level = level.max(strength[u]);
+        core[u] = level;
+        removed[u] = true;
+
+        for v in 0..N {
+            if !removed[v] {
+                strength[v] -= weights[u][v];
+            }
+        }
+    }
+    core
+}
+
+/// Spectral clustering into k market segments
+///
+/// Steps:
+/// - Embed nodes with the k smallest normalized Laplacian eigenvectors
+///   (embeddings::laplacian_spectral), which group communities rather than
+///   the two sides of the bipartite graph
+/// - Scale each node's vector to unit length
+/// - k-means on the vectors, seeded deterministically by farthest-point selection
+///
+/// Output: segment label (0..k) for each node; isolated nodes get segment 0
+pub fn spectral_clusters<const N: usize>(weights: &[[f64; N]; N], k: usize) -> Vec<usize> {
+    let k = k.min(N);
+    if k == 0 {
+        return vec![0; N];
+    }
+
+    let points: Vec<Vec<f64>> = embeddings::laplacian_spectral(weights, k)
+        .into_iter()
+        .map(|p| {
+            let norm = p.iter().map(|v| v * v).sum::<f64>().sqrt();
+            if norm < 1e-15 {
+                p
+            } else {
+                p.iter().map(|v| v / norm).collect()
+            }
+        })
+        .collect();
+
+    let distance =
+        |a: &[f64], b: &[f64]| -> f64 { a.iter().zip(b).map(|(x, y)| (x - y).powi(2)).sum() };
+
+    // Farthest-point seeding: start at node 0, then the node farthest from all centers
+    let mut centers = vec![points[0].clone()];
+    while centers.len() < k {
+        let farthest = (0..N)
+            .max_by(|&a, &b| {
+                let da = centers
+                    .iter()
+                    .map(|c| distance(&points[a], c))
+                    .fold(f64::MAX, f64::min);
+                let db = centers
+                    .iter()
+                    .map(|c| distance(&points[b], c))
+                    .fold(f64::MAX, f64::min);
+                da.total_cmp(&db)
+            })
+            .unwrap_or(0);
+        centers.push(points[farthest].clone());
+    }
+
+    let mut labels = vec![0; N];
+    for _ in 0..100 {
+        let new_labels: Vec<usize> = points
+            .iter()
+            .map(|p| {
+                (0..k)
+                    .min_by(|&a, &b| distance(p, &centers[a]).total_cmp(&distance(p, &centers[b])))
+                    .unwrap_or(0)
+            })
+            .collect();
+
+        let changed = new_labels != labels;
+        labels = new_labels;
+
+        for (c, center) in centers.iter_mut().enumerate() {
+            let members: Vec<&Vec<f64>> = points
+                .iter()
+                .zip(&labels)
+                .filter(|(_, &l)| l == c)
+                .map(|(p, _)| p)
+                .collect();
+            if members.is_empty() {
+                continue;
+            }
+            for (d, v) in center.iter_mut().enumerate() {
+                *v = members.iter().map(|m| m[d]).sum::<f64>() / members.len() as f64;
+            }
+        }
+
+        if !changed {
+            break;
+        }
+    }
+
+    labels
+}
+
+/// Marginal Graph Value of additional fee volume for a user
+///
+/// dGV/dW ≈ (GV(W + ΔW) - GV(W)) / ΔW
+///
+/// EC (x, x̄) and reputation are held fixed: the extra volume is not tied to a
+/// specific counterparty, so its effect on EC is unknown.
+pub fn marginal_gv<const N: usize>(
+    weights: &[[f64; N]; N],
+    ec: &[f64; N],
+    normalized_ec: &[f64; N],
+    reputations: &[f64; N],
+    user: usize,
+    delta_w: f64,
+) -> f64 {
+    if delta_w.abs() < 1e-15 {
+        return marginal_gv_analytic(weights, ec, normalized_ec, reputations, user);
+    }
+
+    let w = graph::total_weight(weights, user);
+    let old_gv = graph::graph_value(w, normalized_ec[user], ec[user], reputations[user]);
+    let new_gv = graph::graph_value(
+        w + delta_w,
+        normalized_ec[user],
+        ec[user],
+        reputations[user],
+    );
+    (new_gv - old_gv) / delta_w
+}
+
+/// Analytic marginal Graph Value: ∂GV/∂W = x̄ · W^(x̄-1) · x^(1-x̄) · r = x̄ · GV / W
+pub fn marginal_gv_analytic<const N: usize>(
+    weights: &[[f64; N]; N],
+    ec: &[f64; N],
+    normalized_ec: &[f64; N],
+    reputations: &[f64; N],
+    user: usize,
+) -> f64 {
+    let w = graph::total_weight(weights, user);
+    graph::graph_value_gradient(w, normalized_ec[user], ec[user], reputations[user]).0
+}
@@ -0,0 +1,78 @@
+//! Backtesting
+//!
+//! Replays the transaction history under a candidate EdgeWeighting and compares
+//! every producer's reward share with the live configuration:
+//!   share_u = GV_u / Σ GV (normalize_graph_values)
+//!
+//! Shadow scoring does the same live: ShadowScoring feeds every transaction to
+//! the primary marketplace and to a copy under the candidate configuration.
+
+use crate::graph;
+use crate::marketplace::{EdgeWeighting, Marketplace};
+use crate::transaction::{Transaction, TransactionLog};
+
+/// Reward shares of the producers under two marketplace states
+///
+/// Output: (producer, share in a, share in b)
+pub fn compare_shares(
+    a: &Marketplace,
+    b: &Marketplace,
+    producers: &[usize],
+) -> Vec<(usize, f64, f64)> {
+    let shares_a = graph::normalize_graph_values(&a.graph_values(producers));
+    let shares_b = graph::normalize_graph_values(&b.graph_values(producers));
+    shares_a
+        .iter()
+        .zip(shares_b)
+        .map(|(&(u, sa), (_, sb))| (u, sa, sb))
+        .collect()
+}
+
+/// Replay the log under a candidate configuration
+///
+/// Output: (producer, baseline share, candidate share)
+pub fn backtest(
+    log: &TransactionLog,
+    candidate: EdgeWeighting,
+    producers: &[usize],
+) -> Vec<(usize, f64, f64)> {
+    compare_shares(&log.replay(), &log.replay_with(candidate), producers)
+}
+
+/// Live marketplace plus a shadow copy running a candidate configuration
+///
+/// Both receive the same transactions; only the primary is meant to be published.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ShadowScoring {
+    pub primary: Marketplace,
+    pub shadow: Marketplace,
+}
+
+impl ShadowScoring {
+    /// Shadow `primary` with a copy of its current state under `candidate`
+    pub fn new(primary: Marketplace, candidate: EdgeWeighting) -> Self {
+        let mut shadow = primary.clone();
+        shadow.set_weighting(candidate);
+        ShadowScoring { primary, shadow }
+    }
+
+    /// Record a transaction in both marketplaces
+    ///
+    /// Returns: Err if buyer and producer are the same user
+    pub fn record(&mut self, transaction: &Transaction) -> Result<(), String> {
+        self.primary.record(transaction)?;
+        self.shadow.record(transaction)
+    }
+
+    /// Divergence per producer: (producer, primary share, shadow share)
+    pub fn divergence(&self, producers: &[usize]) -> Vec<(usize, f64, f64)> {
+        compare_shares(&self.primary, &self.shadow, producers)
+    }
+
+    /// Largest |primary share - shadow share| over the producers
+    pub fn max_divergence(&self, producers: &[usize]) -> f64 {
+        self.divergence(producers)
+            .iter()
+            .fold(0.0, |m, &(_, a, b)| f64::max(m, (a - b).abs()))
+    }
+}
@@ -0,0 +1,38 @@
+//! Score Bands
+//!
+//! Maps a numeric score (reputation or GV) to a configurable descriptor for UI use,
+//! e.g. "New" ≥ 0.0, "Trusted" ≥ 1.0, "Top seller" ≥ 3.0.
+//!
+//! Hysteresis avoids flapping at band edges: a user moves up into a band only once
+//! the score reaches min + h, and drops out of the current band only below min - h.
+
+/// A score band, active from `min` upwards
+#[derive(Clone, Debug, PartialEq)]
+pub struct Band {
+    pub name: String,
+    pub min: f64,
+}
+
+/// Band index for a score without history (highest band with min ≤ score)
+///
+/// bands must be sorted by ascending min; scores below the first band get band 0
+pub fn band_index(bands: &[Band], score: f64) -> usize {
+    bands.iter().rposition(|b| score >= b.min).unwrap_or(0)
+}
+
+/// Band index for a score given the user's current band, with hysteresis h
+pub fn band_with_hysteresis(bands: &[Band], score: f64, current: usize, hysteresis: f64) -> usize {
+    let up = bands
+        .iter()
+        .rposition(|b| score >= b.min + hysteresis)
+        .unwrap_or(0);
+    if up > current {
+        return up;
+    }
+
+    match bands.get(current) {
+        Some(band) if score < band.min - hysteresis => band_index(bands, score),
+        Some(_) => current,
+        None => band_index(bands, score),
+    }
+}
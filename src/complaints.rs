@@ -0,0 +1,67 @@
+//! Complaints
+//!
+//! Complaints are a separate negative signal from ratings: each carries a severity
+//! and decays over time instead of being averaged into the 0.1–5.0 rating channel.
+//!
+//! Penalty after decay (half-life h, complaint age t):
+//!   P_u = Σ severity · 0.5^(t / h)
+//!
+//! Reputation impact:
+//!   r_u' = max(r_min, r_u - impact · P_u)
+//!
+//! The penalty is recomputed from the complaint list, so r_u is the reputation
+//! from reviews only and complaints never accumulate into it.
+//!
+//! Appeals: an upheld appeal marks the complaint as reversed, which removes it
+//! from P_u; the resulting reputation correction is returned to the caller.
+
+use crate::reputation::R_MIN;
+
+/// A complaint against a user
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Complaint {
+    /// How serious the complaint is (e.g. 1.0 = minor, 5.0 = fraud)
+    pub severity: f64,
+    /// Time since the complaint was filed, in the same unit as the half-life
+    pub age: f64,
+    /// Set when an appeal against the complaint was upheld
+    pub reversed: bool,
+}
+
+/// Total decayed penalty: Σ severity · 0.5^(age / half_life)
+pub fn complaint_penalty(complaints: &[Complaint], half_life: f64) -> f64 {
+    if half_life <= 0.0 {
+        return 0.0;
+    }
+    complaints
+        .iter()
+        .filter(|c| !c.reversed)
+        .map(|c| c.severity * 0.5_f64.powf(c.age / half_life))
+        .sum()
+}
+
+/// Apply complaints to a reputation: max(r_min, r - impact · penalty)
+pub fn apply_complaints(
+    reputation: f64,
+    complaints: &[Complaint],
+    half_life: f64,
+    impact: f64,
+) -> f64 {
+    (reputation - impact * complaint_penalty(complaints, half_life)).max(R_MIN)
+}
+
+/// Uphold an appeal against complaints[index]
+///
+/// Returns: the reputation correction Δr ≥ 0 (effective reputation after - before)
+pub fn uphold_appeal(
+    complaints: &mut [Complaint],
+    index: usize,
+    reputation: f64,
+    half_life: f64,
+    impact: f64,
+) -> f64 {
+    let before = apply_complaints(reputation, complaints, half_life, impact);
+    complaints[index].reversed = true;
+    let after = apply_complaints(reputation, complaints, half_life, impact);
+    after - before
+}
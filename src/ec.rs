@@ -1,47 +1,175 @@
-/// Compute Eigenvector Centrality using power iteration
-///
-/// For bipartite graphs, standard power iteration oscillates because
-/// the adjacency matrix has eigenvalues that come in +/- pairs.
-///
-/// Fix: Use A² (squared adjacency matrix) which has only positive eigenvalues.
-/// The eigenvector of A² corresponding to λ_max² is the same as for A.
+//! Compute Eigenvector Centrality using power iteration
+//!
+//! For bipartite graphs, standard power iteration oscillates because
+//! the adjacency matrix has eigenvalues that come in +/- pairs.
+//!
+//! Fix: Use A² (squared adjacency matrix) which has only positive eigenvalues.
+//! The eigenvector of A² corresponding to λ_max² is the same as for A.
 
-/// Compute A² (matrix squared)
-fn square_matrix<const N: usize>(matrix: &[[f64; N]; N]) -> [[f64; N]; N] {
-    let mut result = [[0.0; N]; N];
-    for i in 0..N {
-        for j in 0..N {
-            for k in 0..N {
-                result[i][j] += matrix[i][k] * matrix[k][j];
-            }
-        }
-    }
-    result
-}
+use crate::graph::{Graph, SparseGraph};
 
 /// Compute Eigenvector Centrality using power iteration on A²
 ///
 /// Input: symmetric adjacency matrix where A[i][j] = edge weight between nodes i and j
 /// Output: EC score for each node
 pub fn power_iteration<const N: usize>(matrix: &[[f64; N]; N]) -> [f64; N] {
-    let matrix_squared = square_matrix(matrix);
-    let mut x = [1.0; N];
+    iterate(matrix).0
+}
+
+/// Compute Eigenvector Centrality, reporting failures instead of returning zeros
+///
+/// Returns: Err if the matrix contains NaN/inf weights or power iteration does
+/// not converge within 1000 iterations
+pub fn try_power_iteration<const N: usize>(matrix: &[[f64; N]; N]) -> Result<[f64; N], String> {
+    if matrix.iter().flatten().any(|v| !v.is_finite()) {
+        return Err("matrix contains non-finite weights".to_string());
+    }
+    match iterate(matrix) {
+        (x, true) => Ok(x),
+        (_, false) => Err("power iteration did not converge".to_string()),
+    }
+}
+
+/// Compute Eigenvector Centrality for a dynamically sized graph
+pub fn graph_power_iteration(graph: &Graph) -> Vec<f64> {
+    iterate_rows(graph.rows()).0
+}
+
+/// Compute Eigenvector Centrality for a sparse graph
+///
+/// Each iteration only touches stored edges, O(edges) instead of O(N²).
+pub fn sparse_power_iteration(graph: &SparseGraph) -> Vec<f64> {
+    let n = graph.node_count();
+    if graph.edge_weights().iter().any(|v| !v.is_finite()) {
+        return vec![0.0; n];
+    }
+
+    let max_weight = graph
+        .edge_weights()
+        .iter()
+        .fold(0.0_f64, |m, v| m.max(v.abs()));
+    let multiply = |x: &[f64]| -> Vec<f64> {
+        (0..n)
+            .map(|u| graph.neighbors(u).map(|(v, w)| w * x[v]).sum())
+            .collect()
+    };
+
+    power_iterate(n, max_weight, multiply).0
+}
+
+/// Power iteration on a fixed-size matrix, also reporting whether it converged
+fn iterate<const N: usize>(matrix: &[[f64; N]; N]) -> ([f64; N], bool) {
+    let (x, converged) = iterate_rows(matrix);
+    let mut result = [0.0; N];
+    result.copy_from_slice(&x);
+    (result, converged)
+}
+
+/// Power iteration on the rows of a square adjacency matrix
+///
+/// Returns: (EC scores, converged)
+///
+/// Non-finite input (NaN/inf weights) is rejected up front with all-zero scores.
+fn iterate_rows<R: AsRef<[f64]>>(matrix: &[R]) -> (Vec<f64>, bool) {
+    let n = matrix.len();
+    let weights = || matrix.iter().flat_map(|row| row.as_ref().iter());
+    if weights().any(|v| !v.is_finite()) {
+        return (vec![0.0; n], false);
+    }
+
+    let max_weight = weights().fold(0.0_f64, |m, v| m.max(v.abs()));
+    power_iterate(n, max_weight, |x| multiply_rows(matrix, x))
+}
+
+/// y = A·x for a matrix given by rows
+fn multiply_rows<R: AsRef<[f64]>>(matrix: &[R], x: &[f64]) -> Vec<f64> {
+    matrix
+        .iter()
+        .map(|row| row.as_ref().iter().zip(x).map(|(a, b)| a * b).sum())
+        .collect()
+}
+
+/// Top-k eigenvectors of A² (largest |λ| of A), by power iteration with deflation
+///
+/// Each vector is orthogonalized against the previous ones on every iteration,
+/// so vector 0 is the EC direction. Vectors are unit length with arbitrary sign;
+/// components that vanish under deflation are all zero.
+///
+/// Non-finite input (NaN/inf weights) gives all-zero vectors.
+pub(crate) fn eigenvectors<R: AsRef<[f64]>>(matrix: &[R], k: usize) -> Vec<Vec<f64>> {
+    let n = matrix.len();
+    let weights = || matrix.iter().flat_map(|row| row.as_ref().iter());
+    if weights().any(|v| !v.is_finite()) {
+        return vec![vec![0.0; n]; k.min(n)];
+    }
+
+    let max_weight = weights().fold(0.0_f64, |m, v| m.max(v.abs()));
+    let mut vectors: Vec<Vec<f64>> = Vec::new();
+    for c in 0..k.min(n) {
+        // Distinct start vector so later components are not orthogonalized away
+        let start = (0..n).map(|i| 1.0 + ((i + c) % n) as f64).collect();
+        let (x, _) =
+            deflated_power_iterate(max_weight, |x| multiply_rows(matrix, x), &vectors, start);
+        vectors.push(x);
+    }
+    vectors
+}
+
+/// Power iteration on A², given y = A·x
+///
+/// A²x is computed as A·(A·x), which avoids forming A² explicitly.
+///
+/// EC is scale invariant, so A is scaled to max |w| = 1. This keeps tiny weights
+/// above the 1e-15 norm cutoff and huge weights from overflowing.
+///
+/// Returns: (EC scores, converged)
+fn power_iterate(
+    n: usize,
+    max_weight: f64,
+    multiply: impl Fn(&[f64]) -> Vec<f64>,
+) -> (Vec<f64>, bool) {
+    let (x, converged) = deflated_power_iterate(max_weight, multiply, &[], vec![1.0; n]);
+    (x.iter().map(|v| v.abs()).collect(), converged)
+}
+
+/// Power iteration on A² from `start`, keeping x orthogonal to the unit vectors
+/// in `previous`
+///
+/// Returns: (signed unit vector, converged); all zeros if x vanishes
+fn deflated_power_iterate(
+    max_weight: f64,
+    multiply: impl Fn(&[f64]) -> Vec<f64>,
+    previous: &[Vec<f64>],
+    start: Vec<f64>,
+) -> (Vec<f64>, bool) {
+    let scale = if max_weight > 0.0 { max_weight } else { 1.0 };
+    let mut x = start;
+    let mut converged = false;
 
     for _ in 0..1000 {
-        let mut x_new = [0.0; N];
-        for i in 0..N {
-            for j in 0..N {
-                x_new[i] += matrix_squared[i][j] * x[j];
+        let y: Vec<f64> = multiply(&x).iter().map(|v| v / scale).collect();
+        let mut x_new: Vec<f64> = multiply(&y).iter().map(|v| v / scale).collect();
+
+        // Remove components along the eigenvectors already found
+        for prev in previous {
+            let dot: f64 = x_new.iter().zip(prev).map(|(a, b)| a * b).sum();
+            for (v, p) in x_new.iter_mut().zip(prev) {
+                *v -= dot * p;
             }
         }
 
         let norm: f64 = x_new.iter().map(|v| v * v).sum::<f64>().sqrt();
         if norm < 1e-15 {
+            // EC keeps its start vector on an edgeless graph; a deflated
+            // component that vanishes has no direction left
+            if !previous.is_empty() {
+                x = vec![0.0; x.len()];
+            }
             break;
         }
 
-        for i in 0..N {
-            x_new[i] /= norm;
+        for v in x_new.iter_mut() {
+            *v /= norm;
         }
 
         let diff: f64 = x
@@ -54,20 +182,183 @@ pub fn power_iteration<const N: usize>(matrix: &[[f64; N]; N]) -> [f64; N] {
         x = x_new;
 
         if diff < 1e-10 {
+            converged = true;
             break;
         }
     }
 
-    x.map(|v| v.abs())
+    (x, converged)
+}
+
+/// What to use when power iteration does not converge
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ConvergenceFallback {
+    /// Reuse the EC scores from the last epoch
+    ReuseLast,
+    /// Use degree centrality (row sums of A, unit length)
+    DegreeCentrality,
+    /// Return no scores, so the epoch is aborted
+    Abort,
+}
+
+/// Whether EC came from power iteration or from a fallback
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ConvergenceOutcome {
+    Converged,
+    Fallback(ConvergenceFallback),
+    /// The matrix contains NaN/inf weights; no scores are produced
+    NonFiniteInput,
+}
+
+/// Compute EC, applying a fallback policy if power iteration does not converge
+///
+/// The outcome tells the caller when a fallback was used, e.g. to log or alert.
+///
+/// Returns: (scores, outcome), scores None if the epoch should be aborted
+/// (non-finite input, Abort, or ReuseLast without last_ec)
+pub fn power_iteration_with_fallback<const N: usize>(
+    matrix: &[[f64; N]; N],
+    fallback: ConvergenceFallback,
+    last_ec: Option<&[f64; N]>,
+) -> (Option<[f64; N]>, ConvergenceOutcome) {
+    if matrix.iter().flatten().any(|v| !v.is_finite()) {
+        return (None, ConvergenceOutcome::NonFiniteInput);
+    }
+
+    let (x, converged) = iterate(matrix);
+    if converged {
+        return (Some(x), ConvergenceOutcome::Converged);
+    }
+
+    let scores = match fallback {
+        ConvergenceFallback::ReuseLast => last_ec.copied(),
+        ConvergenceFallback::DegreeCentrality => Some(degree_centrality(matrix)),
+        ConvergenceFallback::Abort => None,
+    };
+    (scores, ConvergenceOutcome::Fallback(fallback))
+}
+
+/// Degree centrality: d_u = Σ w(u,v), scaled to unit length like EC
+pub fn degree_centrality<const N: usize>(matrix: &[[f64; N]; N]) -> [f64; N] {
+    let degrees = matrix.map(|row| row.iter().sum::<f64>());
+    let norm: f64 = degrees.iter().map(|v| v * v).sum::<f64>().sqrt();
+    if norm < 1e-15 {
+        return degrees;
+    }
+    degrees.map(|v| v / norm)
 }
 
 /// Normalize EC scores: x̄_u = x_u / x_max
 ///
 /// Returns values between 0 and 1, where 1 = highest EC in the graph
 pub fn normalize_ec<const N: usize>(ec: &[f64; N]) -> [f64; N] {
+    let mut result = [0.0; N];
+    result.copy_from_slice(&normalize_ec_slice(ec));
+    result
+}
+
+/// Normalize EC scores of a dynamically sized graph: x̄_u = x_u / x_max
+pub fn normalize_ec_slice(ec: &[f64]) -> Vec<f64> {
     let x_max = ec.iter().cloned().fold(0.0_f64, f64::max);
     if x_max < 1e-15 {
-        return *ec;
+        return ec.to_vec();
+    }
+    ec.iter().map(|v| v / x_max).collect()
+}
+
+/// Compute weighted PageRank
+///
+/// x_i = d · (Σ_j x_j · w(j,i) / W_j + D · p_i) + (1 - d) · p_i
+///
+/// Where:
+/// - d = damping factor (typically 0.85)
+/// - W_j = total weight of node j
+/// - D = Σ x_j over dangling nodes (W_j = 0), whose score is redistributed by p
+/// - p = personalization vector (uniform if None), normalized to sum to 1
+///
+/// Unlike EC, every node keeps at least (1 - d) · p_i, so sparse or weakly
+/// connected graphs do not collapse to near-zero scores.
+///
+/// Output: PageRank scores summing to 1
+pub fn pagerank<const N: usize>(
+    matrix: &[[f64; N]; N],
+    damping: f64,
+    personalization: Option<&[f64; N]>,
+) -> [f64; N] {
+    let p = match personalization {
+        Some(p) if p.iter().any(|&v| v > 0.0) => p.map(|v| v.max(0.0)),
+        _ => [1.0; N],
+    };
+    let p_total: f64 = p.iter().sum();
+    let p = p.map(|v| v / p_total);
+
+    let out_weight = matrix.map(|row| row.iter().sum::<f64>());
+    let mut x = p;
+
+    for _ in 0..1000 {
+        let dangling: f64 = (0..N).filter(|&j| out_weight[j] <= 0.0).map(|j| x[j]).sum();
+
+        let mut x_new = [0.0; N];
+        for (i, v) in x_new.iter_mut().enumerate() {
+            let incoming: f64 = (0..N)
+                .filter(|&j| out_weight[j] > 0.0)
+                .map(|j| x[j] * matrix[j][i] / out_weight[j])
+                .sum();
+            *v = damping * (incoming + dangling * p[i]) + (1.0 - damping) * p[i];
+        }
+
+        let diff: f64 = x.iter().zip(x_new.iter()).map(|(a, b)| (a - b).abs()).sum();
+        x = x_new;
+
+        if diff < 1e-10 {
+            break;
+        }
+    }
+
+    x
+}
+
+/// Compute Katz centrality
+///
+/// x = α · A · x + β, solved by iteration, β = 1 for every node
+///
+/// Where:
+/// - α = attenuation factor, must be below 1 / λ_max (largest eigenvalue of A)
+///
+/// Every node gets the baseline β, so new producers with one or two edges no
+/// longer get an EC of essentially zero.
+///
+/// Output: Katz scores scaled to unit length like EC, None if the iteration diverges
+pub fn katz_centrality<const N: usize>(
+    matrix: &[[f64; N]; N],
+    attenuation: f64,
+) -> Option<[f64; N]> {
+    let mut x = [1.0; N];
+
+    for _ in 0..1000 {
+        let mut x_new = [1.0; N];
+        for (i, v) in x_new.iter_mut().enumerate() {
+            *v += attenuation
+                * matrix[i]
+                    .iter()
+                    .zip(x.iter())
+                    .map(|(a, b)| a * b)
+                    .sum::<f64>();
+        }
+
+        if x_new.iter().any(|v| !v.is_finite() || v.abs() > 1e100) {
+            return None;
+        }
+
+        let diff: f64 = x.iter().zip(x_new.iter()).map(|(a, b)| (a - b).abs()).sum();
+        let scale: f64 = x_new.iter().map(|v| v.abs()).sum();
+        x = x_new;
+
+        if diff < 1e-10 * scale {
+            let norm: f64 = x.iter().map(|v| v * v).sum::<f64>().sqrt();
+            return Some(x.map(|v| v / norm));
+        }
     }
-    ec.map(|v| v / x_max)
+
+    None
 }
@@ -0,0 +1,105 @@
+//! Node Embeddings
+//!
+//! Spectral embedding: each node is represented by its entries in the top-k
+//! eigenvectors of A², for use as features in downstream models (e.g. fraud
+//! detection).
+//!
+//! Like EC, the eigenvectors are computed by power iteration on A² (scaled to
+//! max |w| = 1, see ec::eigenvectors), which avoids oscillation on bipartite
+//! graphs. Each vector is orthogonalized against the previous ones (deflation),
+//! so vector 0 is the EC direction.
+//!
+//! A² ranks eigenvectors of A by |λ|, not λ. On a bipartite graph the spectrum of
+//! A is symmetric (±λ), so λ and -λ share an eigenvalue of A² and the embedding
+//! mixes them: it separates the two sides rather than communities.
+//!
+//! Laplacian embedding (for clustering): the top-k eigenvectors of
+//!   M = I + D^(-1/2) · A · D^(-1/2)
+//! i.e. the k smallest eigenvectors of the normalized Laplacian L = 2I - M.
+//! M has eigenvalues 1 + μ with μ in [-1, 1], all ≥ 0, so M² keeps their order and
+//! the bipartite -μ directions come last instead of tying with +μ.
+
+use crate::ec;
+use std::fs::File;
+use std::io::{self, Write};
+
+/// Compute a k-dimensional spectral embedding for every node
+///
+/// Output: one vector of length k per node
+pub fn spectral<const N: usize>(matrix: &[[f64; N]; N], k: usize) -> Vec<Vec<f64>> {
+    to_node_vectors(ec::eigenvectors(matrix, k), N)
+}
+
+/// Compute a k-dimensional normalized Laplacian embedding for every node
+///
+/// D = diagonal of node strengths Σ_v w(u,v); isolated nodes keep only the identity.
+///
+/// Output: one vector of length k per node
+pub fn laplacian_spectral<const N: usize>(matrix: &[[f64; N]; N], k: usize) -> Vec<Vec<f64>> {
+    let inv_sqrt_degree = matrix.map(|row| {
+        let d: f64 = row.iter().sum();
+        if d > 0.0 {
+            1.0 / d.sqrt()
+        } else {
+            0.0
+        }
+    });
+
+    let shifted: Vec<Vec<f64>> = (0..N)
+        .map(|i| {
+            (0..N)
+                .map(|j| {
+                    let identity = if i == j { 1.0 } else { 0.0 };
+                    identity + inv_sqrt_degree[i] * matrix[i][j] * inv_sqrt_degree[j]
+                })
+                .collect()
+        })
+        .collect();
+
+    to_node_vectors(ec::eigenvectors(&shifted, k), N)
+}
+
+/// One row per node from a list of eigenvectors, each signed so that its
+/// largest-magnitude entry is positive
+fn to_node_vectors(mut vectors: Vec<Vec<f64>>, n: usize) -> Vec<Vec<f64>> {
+    for x in vectors.iter_mut() {
+        let largest = x
+            .iter()
+            .cloned()
+            .fold(0.0_f64, |m, v| if v.abs() > m.abs() { v } else { m });
+        if largest < 0.0 {
+            x.iter_mut().for_each(|v| *v = -*v);
+        }
+    }
+
+    (0..n)
+        .map(|node| vectors.iter().map(|v| v[node]).collect())
+        .collect()
+}
+
+/// Write embeddings to a .npy file (float64, shape = nodes × k)
+pub fn write_npy(path: &str, embeddings: &[Vec<f64>]) -> io::Result<()> {
+    let rows = embeddings.len();
+    let cols = embeddings.first().map_or(0, |e| e.len());
+
+    let mut header = format!(
+        "{{'descr': '<f8', 'fortran_order': False, 'shape': ({}, {}), }}",
+        rows, cols
+    );
+    // Magic (6) + version (2) + header length (2) + header must be a multiple of 64
+    while (10 + header.len() + 1) % 64 != 0 {
+        header.push(' ');
+    }
+    header.push('\n');
+
+    let mut file = File::create(path)?;
+    file.write_all(b"\x93NUMPY\x01\x00")?;
+    file.write_all(&(header.len() as u16).to_le_bytes())?;
+    file.write_all(header.as_bytes())?;
+    for row in embeddings {
+        for v in row {
+            file.write_all(&v.to_le_bytes())?;
+        }
+    }
+    Ok(())
+}
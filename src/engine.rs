@@ -0,0 +1,124 @@
+//! Scoring Engine Trait
+//!
+//! The operations an application needs from the scoring engine, so it can swap
+//! the real Marketplace for StubEngine in its own unit tests.
+//!
+//! StubEngine is deterministic and runs no EC solve:
+//! - r = running average of received ratings (R_MIN before the first review)
+//! - GV = W · r, with W the user's total fee volume
+
+use crate::marketplace::Marketplace;
+use crate::reputation;
+
+/// Record transactions and query reputations and Graph Values
+pub trait ScoringEngine {
+    /// Record a transaction and the mutual reviews that follow it
+    ///
+    /// Returns: Err if buyer and producer are the same user
+    fn record_transaction(
+        &mut self,
+        buyer: usize,
+        producer: usize,
+        fee: f64,
+        buyer_rating: f64,
+        producer_rating: f64,
+    ) -> Result<(), String>;
+
+    /// Reputation of a user
+    fn reputation(&self, user: usize) -> f64;
+
+    /// Current Graph Value of a user
+    fn graph_value(&self, user: usize) -> f64;
+
+    /// Current Graph Values of the given producers
+    fn graph_values(&self, producer_indices: &[usize]) -> Vec<(usize, f64)> {
+        producer_indices
+            .iter()
+            .map(|&i| (i, self.graph_value(i)))
+            .collect()
+    }
+}
+
+impl ScoringEngine for Marketplace {
+    fn record_transaction(
+        &mut self,
+        buyer: usize,
+        producer: usize,
+        fee: f64,
+        buyer_rating: f64,
+        producer_rating: f64,
+    ) -> Result<(), String> {
+        Marketplace::record_transaction(self, buyer, producer, fee, buyer_rating, producer_rating)
+    }
+
+    fn reputation(&self, user: usize) -> f64 {
+        Marketplace::reputation(self, user)
+    }
+
+    fn graph_value(&self, user: usize) -> f64 {
+        Marketplace::graph_value(self, user)
+    }
+
+    fn graph_values(&self, producer_indices: &[usize]) -> Vec<(usize, f64)> {
+        Marketplace::graph_values(self, producer_indices)
+    }
+}
+
+/// Deterministic stand-in for Marketplace, without EC
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct StubEngine {
+    reputations: Vec<f64>,
+    tx_counts: Vec<u64>,
+    volumes: Vec<f64>,
+}
+
+impl StubEngine {
+    /// Create a stub with `users` users, no volume and reputation R_MIN
+    pub fn new(users: usize) -> Self {
+        StubEngine {
+            reputations: vec![reputation::R_MIN; users],
+            tx_counts: vec![0; users],
+            volumes: vec![0.0; users],
+        }
+    }
+
+    fn review(&mut self, user: usize, rating: f64) {
+        let n = self.tx_counts[user] as f64;
+        let rating = reputation::clamp_rating(rating);
+        self.reputations[user] = if n == 0.0 {
+            rating
+        } else {
+            (n * self.reputations[user] + rating) / (n + 1.0)
+        };
+        self.tx_counts[user] += 1;
+    }
+}
+
+impl ScoringEngine for StubEngine {
+    fn record_transaction(
+        &mut self,
+        buyer: usize,
+        producer: usize,
+        fee: f64,
+        buyer_rating: f64,
+        producer_rating: f64,
+    ) -> Result<(), String> {
+        if buyer == producer {
+            return Err(format!("user {} cannot transact with itself", buyer));
+        }
+
+        self.volumes[buyer] += fee;
+        self.volumes[producer] += fee;
+        self.review(producer, buyer_rating);
+        self.review(buyer, producer_rating);
+        Ok(())
+    }
+
+    fn reputation(&self, user: usize) -> f64 {
+        self.reputations[user]
+    }
+
+    fn graph_value(&self, user: usize) -> f64 {
+        self.volumes[user] * self.reputations[user]
+    }
+}
@@ -0,0 +1,91 @@
+//! Governance Vote Weights
+//!
+//! Converts Graph Values into voting weights for a DAO voting module.
+//!
+//! Steps:
+//! - Base weight: GV (linear) or √GV (quadratic, dampens large holders)
+//! - Normalize base weights to sum to 1.0
+//! - Cap each account at max_share and redistribute the excess
+//!   proportionally among the accounts still under the cap
+//!
+//! If max_share · accounts < 1.0 every account ends up capped and the
+//! weights sum to less than 1.0.
+
+/// How a Graph Value maps to a base voting weight
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum VoteScaling {
+    /// weight ∝ GV
+    Linear,
+    /// weight ∝ √GV
+    Quadratic,
+}
+
+/// Calculate voting weights from Graph Values
+///
+/// Input:
+/// - gvs: (index, graph value) pairs
+/// - max_share: maximum fraction of total weight per account (e.g. 0.05)
+/// - scaling: linear or quadratic base weight
+///
+/// Output: (index, voting weight) with each weight ≤ max_share
+///
+/// Returns: Err if max_share is outside (0, 1]
+pub fn vote_weights(
+    gvs: &[(usize, f64)],
+    max_share: f64,
+    scaling: VoteScaling,
+) -> Result<Vec<(usize, f64)>, String> {
+    if !(max_share > 0.0 && max_share <= 1.0) {
+        return Err(format!("max_share {} is outside (0, 1]", max_share));
+    }
+
+    let base: Vec<f64> = gvs
+        .iter()
+        .map(|&(_, gv)| match scaling {
+            VoteScaling::Linear => gv.max(0.0),
+            VoteScaling::Quadratic => gv.max(0.0).sqrt(),
+        })
+        .collect();
+
+    let total: f64 = base.iter().sum();
+    if total < 1e-15 {
+        return Ok(gvs.iter().map(|&(i, _)| (i, 0.0)).collect());
+    }
+
+    let mut weights: Vec<f64> = base.iter().map(|b| b / total).collect();
+    let mut capped = vec![false; weights.len()];
+
+    // Water-filling: cap, then hand the excess to uncapped accounts
+    loop {
+        let mut excess = 0.0;
+        for (w, c) in weights.iter_mut().zip(capped.iter_mut()) {
+            if !*c && *w > max_share {
+                excess += *w - max_share;
+                *w = max_share;
+                *c = true;
+            }
+        }
+
+        if excess < 1e-15 {
+            break;
+        }
+
+        let uncapped_total: f64 = weights
+            .iter()
+            .zip(capped.iter())
+            .filter(|(_, &c)| !c)
+            .map(|(w, _)| w)
+            .sum();
+        if uncapped_total < 1e-15 {
+            break;
+        }
+
+        for (w, &c) in weights.iter_mut().zip(capped.iter()) {
+            if !c {
+                *w += excess * *w / uncapped_total;
+            }
+        }
+    }
+
+    Ok(gvs.iter().zip(weights).map(|(&(i, _), w)| (i, w)).collect())
+}
@@ -1,18 +1,21 @@
-/// Graph Value Calculation
-///
-/// From the paper, Graph Value measures a producer's contribution to the network.
-/// Formula: GV = W^x̄ · x^(1-x̄) · r
-///
-/// Where:
-/// - W = total edge weight for producer (sum of all transaction fees)
-/// - x̄ = normalized EC score (0 to 1, where 1 = highest EC in graph)
-/// - x = raw EC score
-/// - r = reputation score (from peer reviews)
-///
-/// Intuition:
-/// - High EC (x̄ → 1): GV ≈ W · r (volume and reputation matter most)
-/// - Low EC (x̄ → 0): GV ≈ x · r (connectivity matters most)
-/// - This balances between rewarding volume vs connectivity
+//! Graph Value Calculation
+//!
+//! From the paper, Graph Value measures a producer's contribution to the network.
+//! Formula: GV = W^x̄ · x^(1-x̄) · r
+//!
+//! Where:
+//! - W = total edge weight for producer (sum of all transaction fees)
+//! - x̄ = normalized EC score (0 to 1, where 1 = highest EC in graph)
+//! - x = raw EC score
+//! - r = reputation score (from peer reviews)
+//!
+//! Intuition:
+//! - High EC (x̄ → 1): GV ≈ W · r (volume and reputation matter most)
+//! - Low EC (x̄ → 0): GV ≈ x · r (connectivity matters most)
+//! - This balances between rewarding volume vs connectivity
+
+use crate::ec;
+use crate::reputation;
 
 /// Calculate Graph Value for a single producer
 ///
@@ -33,6 +36,37 @@ pub fn graph_value(total_weight: f64, normalized_ec: f64, raw_ec: f64, reputatio
     weight_term * ec_term * reputation
 }
 
+/// Partial derivatives of Graph Value
+///
+/// With GV = W^x̄ · x^(1-x̄) · r, each variable treated as independent:
+/// - ∂GV/∂W = x̄ · GV / W
+/// - ∂GV/∂x = (1-x̄) · GV / x, holding x̄ fixed
+/// - ∂GV/∂x̄ = GV · (ln W - ln x)
+/// - ∂GV/∂r = W^x̄ · x^(1-x̄)
+///
+/// x̄ = x / x_max also depends on x. For a user other than the EC maximum,
+/// dGV/dx = ∂GV/∂x + ∂GV/∂x̄ / x_max.
+///
+/// Returns: (∂GV/∂W, ∂GV/∂x, ∂GV/∂x̄, ∂GV/∂r), all zero where GV is zeroed by the edge cases
+pub fn graph_value_gradient(
+    total_weight: f64,
+    normalized_ec: f64,
+    raw_ec: f64,
+    reputation: f64,
+) -> (f64, f64, f64, f64) {
+    if total_weight <= 0.0 || raw_ec <= 0.0 || reputation <= 0.0 {
+        return (0.0, 0.0, 0.0, 0.0);
+    }
+
+    let gv = graph_value(total_weight, normalized_ec, raw_ec, reputation);
+    (
+        normalized_ec * gv / total_weight,
+        (1.0 - normalized_ec) * gv / raw_ec,
+        gv * (total_weight.ln() - raw_ec.ln()),
+        gv / reputation,
+    )
+}
+
 /// Calculate total edge weight for a node in the graph
 ///
 /// W_u = Σ w(u,v) for all neighbors v
@@ -56,17 +90,303 @@ pub fn graph_values<const N: usize>(
     normalized_ec: &[f64; N],
     reputations: &[f64; N],
     producer_indices: &[usize],
+) -> Vec<(usize, f64)> {
+    graph_values_by(
+        |i| total_weight(weights, i),
+        ec,
+        normalized_ec,
+        reputations,
+        producer_indices,
+    )
+}
+
+/// Graph Values of the producers, with W_u given by a closure so every graph
+/// representation shares one implementation
+fn graph_values_by(
+    total_weight: impl Fn(usize) -> f64,
+    ec: &[f64],
+    normalized_ec: &[f64],
+    reputations: &[f64],
+    producer_indices: &[usize],
 ) -> Vec<(usize, f64)> {
     producer_indices
         .iter()
         .map(|&i| {
-            let w = total_weight(weights, i);
-            let gv = graph_value(w, normalized_ec[i], ec[i], reputations[i]);
+            let gv = graph_value(total_weight(i), normalized_ec[i], ec[i], reputations[i]);
             (i, gv)
         })
         .collect()
 }
 
+/// Adjacency matrix with a runtime-determined, growing number of nodes
+///
+/// Edges are undirected: add_edge updates both w(u,v) and w(v,u).
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Graph {
+    weights: Vec<Vec<f64>>,
+}
+
+impl Graph {
+    /// Create a graph with `nodes` nodes and no edges
+    pub fn new(nodes: usize) -> Self {
+        Graph {
+            weights: vec![vec![0.0; nodes]; nodes],
+        }
+    }
+
+    /// Create a graph from a fixed-size adjacency matrix
+    pub fn from_matrix<const N: usize>(weights: &[[f64; N]; N]) -> Self {
+        Graph {
+            weights: weights.iter().map(|row| row.to_vec()).collect(),
+        }
+    }
+
+    /// Number of nodes
+    pub fn node_count(&self) -> usize {
+        self.weights.len()
+    }
+
+    /// Add a node without edges, returning its index
+    pub fn add_node(&mut self) -> usize {
+        for row in self.weights.iter_mut() {
+            row.push(0.0);
+        }
+        let n = self.weights.len() + 1;
+        self.weights.push(vec![0.0; n]);
+        n - 1
+    }
+
+    /// Add weight to the edge between u and v (e.g. a transaction fee)
+    pub fn add_edge(&mut self, u: usize, v: usize, weight: f64) {
+        self.weights[u][v] += weight;
+        if u != v {
+            self.weights[v][u] += weight;
+        }
+    }
+
+    /// Remove the edge between u and v
+    pub fn remove_edge(&mut self, u: usize, v: usize) {
+        self.weights[u][v] = 0.0;
+        self.weights[v][u] = 0.0;
+    }
+
+    /// Edge weight w(u,v), 0 if there is no edge
+    pub fn weight(&self, u: usize, v: usize) -> f64 {
+        self.weights[u][v]
+    }
+
+    /// W_u = Σ w(u,v) for all neighbors v
+    pub fn total_weight(&self, node: usize) -> f64 {
+        self.weights[node].iter().sum()
+    }
+
+    /// Rows of the adjacency matrix
+    pub fn rows(&self) -> &[Vec<f64>] {
+        &self.weights
+    }
+}
+
+/// Calculate Graph Value for all producers of a dynamically sized graph
+///
+/// EC and reputations are slices indexed by node.
+pub fn dynamic_graph_values(
+    graph: &Graph,
+    ec: &[f64],
+    normalized_ec: &[f64],
+    reputations: &[f64],
+    producer_indices: &[usize],
+) -> Vec<(usize, f64)> {
+    graph_values_by(
+        |i| graph.total_weight(i),
+        ec,
+        normalized_ec,
+        reputations,
+        producer_indices,
+    )
+}
+
+/// Sparse adjacency in CSR (compressed sparse row) form
+///
+/// For large marketplaces most pairs never transact, so only non-zero edges are stored
+/// (build directly from an edge list with from_edges):
+/// - neighbors[offsets[u]..offsets[u + 1]] = neighbors of u
+/// - weights[offsets[u]..offsets[u + 1]] = matching edge weights
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SparseGraph {
+    offsets: Vec<usize>,
+    neighbors: Vec<usize>,
+    weights: Vec<f64>,
+}
+
+impl SparseGraph {
+    /// Build from the rows of a dense adjacency matrix, keeping non-zero entries
+    fn from_rows<R: AsRef<[f64]>>(rows: &[R]) -> Self {
+        let mut sparse = SparseGraph {
+            offsets: vec![0],
+            neighbors: Vec::new(),
+            weights: Vec::new(),
+        };
+        for row in rows {
+            for (v, &w) in row.as_ref().iter().enumerate() {
+                if w != 0.0 {
+                    sparse.neighbors.push(v);
+                    sparse.weights.push(w);
+                }
+            }
+            sparse.offsets.push(sparse.neighbors.len());
+        }
+        sparse
+    }
+
+    /// Build from an undirected edge list without allocating the dense form
+    ///
+    /// Each (u, v, w) adds w to w(u,v) and w(v,u), like Graph::add_edge. Edges are
+    /// sorted, duplicates are summed and CSR is built in one pass, O(E log E).
+    ///
+    /// Returns: Err if an edge refers to a node ≥ nodes
+    pub fn from_edges(nodes: usize, edges: &[(usize, usize, f64)]) -> Result<Self, String> {
+        let mut directed = Vec::with_capacity(2 * edges.len());
+        for &(u, v, w) in edges {
+            if u >= nodes || v >= nodes {
+                return Err(format!(
+                    "edge ({}, {}) is outside the {} nodes",
+                    u, v, nodes
+                ));
+            }
+            directed.push((u, v, w));
+            if u != v {
+                directed.push((v, u, w));
+            }
+        }
+        directed.sort_by_key(|&(u, v, _)| (u, v));
+
+        let mut sparse = SparseGraph {
+            offsets: vec![0; nodes + 1],
+            neighbors: Vec::new(),
+            weights: Vec::new(),
+        };
+        let mut last = None;
+        for (u, v, w) in directed {
+            if last == Some((u, v)) {
+                *sparse.weights.last_mut().unwrap() += w;
+            } else {
+                sparse.neighbors.push(v);
+                sparse.weights.push(w);
+                sparse.offsets[u + 1] = sparse.neighbors.len();
+                last = Some((u, v));
+            }
+        }
+        // Nodes without edges end where the previous node ended
+        for u in 1..=nodes {
+            sparse.offsets[u] = sparse.offsets[u].max(sparse.offsets[u - 1]);
+        }
+        Ok(sparse)
+    }
+
+    /// Convert from a fixed-size dense matrix
+    pub fn from_matrix<const N: usize>(weights: &[[f64; N]; N]) -> Self {
+        Self::from_rows(weights)
+    }
+
+    /// Convert from a dense Graph
+    pub fn from_graph(graph: &Graph) -> Self {
+        Self::from_rows(graph.rows())
+    }
+
+    /// Convert back to a dense Graph
+    pub fn to_graph(&self) -> Graph {
+        let mut graph = Graph::new(self.node_count());
+        for u in 0..self.node_count() {
+            for (v, w) in self.neighbors(u) {
+                graph.weights[u][v] = w;
+            }
+        }
+        graph
+    }
+
+    /// Number of nodes
+    pub fn node_count(&self) -> usize {
+        self.offsets.len() - 1
+    }
+
+    /// (neighbor, weight) pairs of a node
+    pub fn neighbors(&self, node: usize) -> impl Iterator<Item = (usize, f64)> + '_ {
+        let range = self.offsets[node]..self.offsets[node + 1];
+        self.neighbors[range.clone()]
+            .iter()
+            .copied()
+            .zip(self.weights[range].iter().copied())
+    }
+
+    /// W_u = Σ w(u,v) for all neighbors v
+    pub fn total_weight(&self, node: usize) -> f64 {
+        self.weights[self.offsets[node]..self.offsets[node + 1]]
+            .iter()
+            .sum()
+    }
+
+    /// Stored edge weights
+    pub fn edge_weights(&self) -> &[f64] {
+        &self.weights
+    }
+}
+
+/// Bulk loader for the initial import of a transaction graph
+///
+/// Edges are only collected on insert; sorting, deduplication and CSR construction
+/// happen once in build, instead of an O(N) row update per Graph::add_edge.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GraphBuilder {
+    nodes: usize,
+    edges: Vec<(usize, usize, f64)>,
+}
+
+impl GraphBuilder {
+    /// Create a builder for a graph with `nodes` nodes
+    pub fn new(nodes: usize) -> Self {
+        GraphBuilder {
+            nodes,
+            edges: Vec::new(),
+        }
+    }
+
+    /// Queue weight for the undirected edge between u and v
+    pub fn add_edge(&mut self, u: usize, v: usize, weight: f64) {
+        self.edges.push((u, v, weight));
+    }
+
+    /// Queue many edges at once
+    pub fn extend(&mut self, edges: &[(usize, usize, f64)]) {
+        self.edges.extend_from_slice(edges);
+    }
+
+    /// Build the sparse graph (see SparseGraph::from_edges)
+    ///
+    /// Returns: Err if an edge refers to a node ≥ nodes
+    pub fn build(&self) -> Result<SparseGraph, String> {
+        SparseGraph::from_edges(self.nodes, &self.edges)
+    }
+}
+
+/// Calculate Graph Value for all producers of a sparse graph
+///
+/// W_u only sums the stored edges of u.
+pub fn sparse_graph_values(
+    graph: &SparseGraph,
+    ec: &[f64],
+    normalized_ec: &[f64],
+    reputations: &[f64],
+    producer_indices: &[usize],
+) -> Vec<(usize, f64)> {
+    graph_values_by(
+        |i| graph.total_weight(i),
+        ec,
+        normalized_ec,
+        reputations,
+        producer_indices,
+    )
+}
+
 /// Normalize Graph Values to sum to 1.0 (for reward distribution)
 ///
 /// Returns the fraction of total rewards each producer should receive
@@ -80,6 +400,171 @@ pub fn normalize_graph_values(gvs: &[(usize, f64)]) -> Vec<(usize, f64)> {
     gvs.iter().map(|&(i, gv)| (i, gv / total)).collect()
 }
 
+/// Normalize Graph Values within producer cohorts, each with its own reward budget
+///
+/// share_u = budget_c · GV_u / Σ GV over cohort c
+///
+/// Input:
+/// - cohorts: cohort index per entry of gvs (category, region, tenure band, ...)
+/// - budgets: fraction of the reward pool per cohort (should sum to 1.0)
+///
+/// A cohort with zero total GV leaves its budget undistributed.
+///
+/// Returns: Err if cohorts and gvs differ in length or a cohort has no budget
+pub fn normalize_by_cohort(
+    gvs: &[(usize, f64)],
+    cohorts: &[usize],
+    budgets: &[f64],
+) -> Result<Vec<(usize, f64)>, String> {
+    if cohorts.len() != gvs.len() {
+        return Err(format!(
+            "{} cohorts for {} graph values",
+            cohorts.len(),
+            gvs.len()
+        ));
+    }
+    if let Some(&c) = cohorts.iter().find(|&&c| c >= budgets.len()) {
+        return Err(format!("cohort {} has no budget", c));
+    }
+
+    let mut cohort_totals = vec![0.0; budgets.len()];
+    for (&(_, gv), &c) in gvs.iter().zip(cohorts) {
+        cohort_totals[c] += gv;
+    }
+
+    Ok(gvs
+        .iter()
+        .zip(cohorts)
+        .map(|(&(i, gv), &c)| {
+            if cohort_totals[c] < 1e-15 {
+                (i, 0.0)
+            } else {
+                (i, budgets[c] * gv / cohort_totals[c])
+            }
+        })
+        .collect())
+}
+
+/// Split rewards between a volume pool and a quality pool
+///
+/// share_u = split · GV_u / Σ GV + (1 - split) · r_u N_u / Σ r N
+///
+/// Where:
+/// - split = fraction of rewards in the volume (GV) pool
+/// - r_u N_u = reputation-weighted transaction count (quality pool)
+pub fn dual_pool_shares<const N: usize>(
+    gvs: &[(usize, f64)],
+    reputations: &[f64; N],
+    tx_counts: &[u64; N],
+    volume_split: f64,
+) -> Vec<(usize, f64)> {
+    let split = volume_split.clamp(0.0, 1.0);
+    let quality: Vec<(usize, f64)> = gvs
+        .iter()
+        .map(|&(i, _)| (i, reputations[i] * tx_counts[i] as f64))
+        .collect();
+
+    normalize_graph_values(gvs)
+        .iter()
+        .zip(normalize_graph_values(&quality))
+        .map(|(&(i, v), (_, q))| (i, split * v + (1.0 - split) * q))
+        .collect()
+}
+
+/// Fairness constraints on reward shares
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ShareConstraints {
+    /// Maximum share per producer
+    pub max_share: f64,
+    /// Minimum share for any producer whose activity is at least activity_threshold
+    pub min_share: f64,
+    pub activity_threshold: f64,
+}
+
+/// Apply fairness constraints to normalized reward shares
+///
+/// f_u = clamp(λ · s_u, lo_u, max_share), lo_u = min_share for active producers, else 0
+///
+/// λ is found by bisection so that Σ f_u = 1: capped excess is redistributed
+/// proportionally to the uncapped producers, and minimum shares are funded the same way.
+///
+/// Input:
+/// - shares: output of normalize_graph_values
+/// - activity: activity measure per entry of shares (e.g. transaction count)
+///
+/// Returns: Err if activity and shares differ in length, or the constraints cannot
+/// all hold while summing to 1
+pub fn constrained_shares(
+    shares: &[(usize, f64)],
+    activity: &[f64],
+    constraints: ShareConstraints,
+) -> Result<Vec<(usize, f64)>, String> {
+    if activity.len() != shares.len() {
+        return Err(format!(
+            "{} activity values for {} shares",
+            activity.len(),
+            shares.len()
+        ));
+    }
+
+    let lower: Vec<f64> = activity
+        .iter()
+        .map(|&a| {
+            if a >= constraints.activity_threshold {
+                constraints.min_share
+            } else {
+                0.0
+            }
+        })
+        .collect();
+
+    let constrained = |lambda: f64| -> Vec<f64> {
+        shares
+            .iter()
+            .zip(lower.iter())
+            .map(|(&(_, s), &lo)| (lambda * s).clamp(lo, constraints.max_share.max(lo)))
+            .collect()
+    };
+    let total = |lambda: f64| -> f64 { constrained(lambda).iter().sum() };
+
+    if constraints.min_share > constraints.max_share {
+        return Err("min_share is larger than max_share".to_string());
+    }
+    if total(0.0) > 1.0 + 1e-12 {
+        return Err("minimum shares exceed the reward pool".to_string());
+    }
+
+    // At lambda_hi every producer with a positive share is capped
+    let min_positive = shares
+        .iter()
+        .map(|&(_, s)| s)
+        .filter(|&s| s > 0.0)
+        .fold(f64::MAX, f64::min);
+    if min_positive == f64::MAX {
+        return Err("no producer has a positive share".to_string());
+    }
+    let mut lambda_hi = constraints.max_share / min_positive;
+    if total(lambda_hi) < 1.0 - 1e-12 {
+        return Err("max_share is too small to distribute the whole pool".to_string());
+    }
+
+    let mut lambda_lo = 0.0;
+    for _ in 0..200 {
+        let mid = (lambda_lo + lambda_hi) / 2.0;
+        if total(mid) < 1.0 {
+            lambda_lo = mid;
+        } else {
+            lambda_hi = mid;
+        }
+    }
+
+    Ok(shares
+        .iter()
+        .zip(constrained(lambda_hi))
+        .map(|(&(i, _), f)| (i, f))
+        .collect())
+}
+
 /// Bare graph value change after a transaction
 ///
 /// ΔG_u = (W_u + ΔW_u)^x̄ · (x_u + Δx_u)^(1-x̄) · (r_u + Δr_u) - W_u^x̄ · x_u^(1-x̄) · r_u
@@ -121,3 +606,550 @@ pub fn performance_multiplier(delta_g: f64, delta_w: f64, average_ratio: f64) ->
     let b_u = 2.0 * numerator / denominator;
     (1.0 + b_u).max(0.0)
 }
+
+/// Formula used for the performance multiplier α_u
+///
+/// r_u = ΔG_u/ΔW_u, avg = mean of r_u, s = mean of |r_u| (scale of the epoch's ratios)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AlphaFormula {
+    /// α_u = Max(0, 1 + 2 * (r_u - avg) / (|r_u| + |avg|)), see performance_multiplier
+    Relative,
+    /// α_u = clamp(1 + (r_u - avg) / s, 0, max)
+    ClippedLinear { max: f64 },
+    /// α_u = 2 / (1 + e^(-steepness · (r_u - avg) / s))
+    Sigmoid { steepness: f64 },
+    /// α_u = 2 · rank_u / (n - 1), rank_u = position of r_u in ascending order
+    Rank,
+}
+
+/// Guardrails applied to ΔG/ΔW ratios before the epoch average is taken
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RatioGuardrails {
+    /// Users with |ΔW_u| below this are treated as ΔW_u = 0 (α_u = 0, not averaged)
+    pub min_delta_w: f64,
+    /// Fraction clipped at each tail before averaging (0 = off, 0.05 = 5th..95th percentile)
+    pub winsor_fraction: f64,
+}
+
+/// Epoch diagnostics for the ratio average
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RatioDiagnostics {
+    /// avg used by the α formula
+    pub average_ratio: f64,
+    /// Users excluded by min_delta_w (with ΔW ≠ 0)
+    pub excluded: usize,
+    /// Users whose ratio was clipped by winsorization
+    pub winsorized: usize,
+    /// Winsorization bounds
+    pub lower: f64,
+    pub upper: f64,
+}
+
+/// Calculate α_u for every user of an epoch with the chosen formula
+///
+/// Input: (ΔG_u, ΔW_u) per user
+///
+/// Output: α_u per user (users with ΔW_u = 0 or below min_delta_w get 0) and diagnostics
+pub fn performance_multipliers(
+    deltas: &[(f64, f64)],
+    formula: AlphaFormula,
+    guardrails: RatioGuardrails,
+) -> (Vec<f64>, RatioDiagnostics) {
+    let min_delta_w = guardrails.min_delta_w.max(1e-15);
+    let ratios: Vec<Option<f64>> = deltas
+        .iter()
+        .map(|&(dg, dw)| (dw.abs() >= min_delta_w).then(|| dg / dw))
+        .collect();
+    let excluded = deltas
+        .iter()
+        .filter(|&&(_, dw)| dw.abs() >= 1e-15 && dw.abs() < min_delta_w)
+        .count();
+
+    let mut sorted: Vec<f64> = ratios.iter().flatten().copied().collect();
+    if sorted.is_empty() {
+        let diagnostics = RatioDiagnostics {
+            average_ratio: 0.0,
+            excluded,
+            winsorized: 0,
+            lower: 0.0,
+            upper: 0.0,
+        };
+        return (vec![0.0; deltas.len()], diagnostics);
+    }
+    sorted.sort_by(f64::total_cmp);
+
+    // Winsorize: clip the k = floor(p · n) lowest and highest ratios before averaging
+    let n = sorted.len();
+    let p = guardrails.winsor_fraction.clamp(0.0, 0.5);
+    let k = ((p * n as f64).floor() as usize).min((n - 1) / 2);
+    let lower = sorted[k];
+    let upper = sorted[n - 1 - k];
+    let winsorized = sorted.iter().filter(|&&r| r < lower || r > upper).count();
+    let valid: Vec<f64> = sorted.iter().map(|r| r.clamp(lower, upper)).collect();
+
+    let n = valid.len() as f64;
+    let avg = valid.iter().sum::<f64>() / n;
+    let scale = valid.iter().map(|r| r.abs()).sum::<f64>() / n;
+    let diagnostics = RatioDiagnostics {
+        average_ratio: avg,
+        excluded,
+        winsorized,
+        lower,
+        upper,
+    };
+
+    let multipliers = ratios
+        .iter()
+        .zip(deltas)
+        .map(|(ratio, &(dg, dw))| {
+            let r = match ratio {
+                Some(r) => *r,
+                None => return 0.0,
+            };
+            match formula {
+                AlphaFormula::Relative => performance_multiplier(dg, dw, avg),
+                AlphaFormula::ClippedLinear { max } => {
+                    if scale < 1e-15 {
+                        1.0
+                    } else {
+                        (1.0 + (r - avg) / scale).clamp(0.0, max)
+                    }
+                }
+                AlphaFormula::Sigmoid { steepness } => {
+                    if scale < 1e-15 {
+                        1.0
+                    } else {
+                        2.0 / (1.0 + (-steepness * (r - avg) / scale).exp())
+                    }
+                }
+                AlphaFormula::Rank => {
+                    if valid.len() < 2 {
+                        1.0
+                    } else {
+                        let rank = sorted.iter().filter(|&&v| v < r).count() as f64;
+                        2.0 * rank / (n - 1.0)
+                    }
+                }
+            }
+        })
+        .collect();
+
+    (multipliers, diagnostics)
+}
+
+/// Calculate Graph Value for an organization made of member producers
+///
+/// Aggregation (anti-double-count: edges between members are internal and excluded):
+/// - W_org = Σ w(u,v) for members u and non-members v
+/// - r_org = Σ W_u · r_u / Σ W_u (weight-weighted average reputation)
+/// - x_org, x̄_org = weight-weighted average of member raw / normalized EC
+///
+/// Returns: W_org^x̄_org · x_org^(1-x̄_org) · r_org
+pub fn org_graph_value<const N: usize>(
+    weights: &[[f64; N]; N],
+    ec: &[f64; N],
+    normalized_ec: &[f64; N],
+    reputations: &[f64; N],
+    members: &[usize],
+) -> f64 {
+    let mut org_weight = 0.0;
+    let mut member_weight_total = 0.0;
+    let mut rep_sum = 0.0;
+    let mut ec_sum = 0.0;
+    let mut norm_ec_sum = 0.0;
+
+    for &u in members {
+        let external: f64 = weights[u]
+            .iter()
+            .enumerate()
+            .filter(|(v, _)| !members.contains(v))
+            .map(|(_, w)| w)
+            .sum();
+        let w_u = total_weight(weights, u);
+
+        org_weight += external;
+        member_weight_total += w_u;
+        rep_sum += w_u * reputations[u];
+        ec_sum += w_u * ec[u];
+        norm_ec_sum += w_u * normalized_ec[u];
+    }
+
+    if member_weight_total < 1e-15 {
+        return 0.0;
+    }
+
+    graph_value(
+        org_weight,
+        norm_ec_sum / member_weight_total,
+        ec_sum / member_weight_total,
+        rep_sum / member_weight_total,
+    )
+}
+
+/// Calculate Graph Value for all organizations
+///
+/// Input: orgs[k] = member producer indices of organization k
+///
+/// Output: (organization index, org Graph Value)
+pub fn org_graph_values<const N: usize>(
+    weights: &[[f64; N]; N],
+    ec: &[f64; N],
+    normalized_ec: &[f64; N],
+    reputations: &[f64; N],
+    orgs: &[Vec<usize>],
+) -> Vec<(usize, f64)> {
+    orgs.iter()
+        .enumerate()
+        .map(|(k, members)| {
+            (
+                k,
+                org_graph_value(weights, ec, normalized_ec, reputations, members),
+            )
+        })
+        .collect()
+}
+
+/// Restrict the graph to one region
+///
+/// Keeps only edges where both endpoints are tagged with `region`; untagged
+/// nodes and cross-region edges are dropped.
+pub fn restrict_to_region<const N: usize>(
+    weights: &[[f64; N]; N],
+    regions: &[Option<u32>; N],
+    region: u32,
+) -> [[f64; N]; N] {
+    let mut restricted = [[0.0; N]; N];
+    for i in 0..N {
+        for j in 0..N {
+            if regions[i] == Some(region) && regions[j] == Some(region) {
+                restricted[i][j] = weights[i][j];
+            }
+        }
+    }
+    restricted
+}
+
+/// Regional leaderboard: Graph Values computed with region-restricted EC and weights
+///
+/// Regions here are per-user tags. To restrict by the region of each transaction
+/// instead, use TransactionLog::replay_region.
+///
+/// Output: (producer, graph value) for producers in the region, highest first
+pub fn region_graph_values<const N: usize>(
+    weights: &[[f64; N]; N],
+    regions: &[Option<u32>; N],
+    region: u32,
+    reputations: &[f64; N],
+    producer_indices: &[usize],
+) -> Vec<(usize, f64)> {
+    let restricted = restrict_to_region(weights, regions, region);
+    let region_ec = ec::power_iteration(&restricted);
+    let region_norm_ec = ec::normalize_ec(&region_ec);
+
+    let producers: Vec<usize> = producer_indices
+        .iter()
+        .copied()
+        .filter(|&i| regions[i] == Some(region))
+        .collect();
+
+    let mut gvs = graph_values(
+        &restricted,
+        &region_ec,
+        &region_norm_ec,
+        reputations,
+        &producers,
+    );
+    gvs.sort_by(|a, b| b.1.total_cmp(&a.1));
+    gvs
+}
+
+/// Public view of a leaderboard: drop users who opted out of public ranking
+///
+/// Only the published list changes. Scores, EC and reputation updates still use
+/// every user, so opted-out users keep influencing their counterparties.
+///
+/// Input: opted_out = privacy flag per user index; users beyond it have not opted out
+pub fn public_leaderboard(leaderboard: &[(usize, f64)], opted_out: &[bool]) -> Vec<(usize, f64)> {
+    leaderboard
+        .iter()
+        .copied()
+        .filter(|&(i, _)| !opted_out.get(i).copied().unwrap_or(false))
+        .collect()
+}
+
+/// Edge weight contribution of a service transaction, blending fee and service hours
+///
+/// w = (1 - blend) · fee + blend · hours · hour_value
+///
+/// Where:
+/// - hour_value = fee-equivalent value of one service hour
+/// - blend = 0 uses only the fee, 1 uses only the hours
+///
+/// Transactions without a duration contribute their fee only.
+pub fn service_weight(fee: f64, duration_hours: Option<f64>, hour_value: f64, blend: f64) -> f64 {
+    match duration_hours {
+        Some(hours) => {
+            let blend = blend.clamp(0.0, 1.0);
+            (1.0 - blend) * fee + blend * hours * hour_value
+        }
+        None => fee,
+    }
+}
+
+/// How a transaction fee maps to an edge weight contribution
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WeightMode {
+    /// w = fee
+    Fee,
+    /// w = fee · (rating / r_max), so badly rated volume adds little centrality
+    QualityAdjusted,
+}
+
+/// Edge weight contribution of a transaction
+///
+/// rating is the rating the producer received (clamped to r_min..r_max)
+pub fn edge_weight(fee: f64, rating: f64, mode: WeightMode) -> f64 {
+    match mode {
+        WeightMode::Fee => fee,
+        WeightMode::QualityAdjusted => fee * reputation::clamp_rating(rating) / reputation::R_MAX,
+    }
+}
+
+/// Transaction details a custom weight function can use
+#[derive(Clone, Debug, PartialEq)]
+pub struct EdgeMeta {
+    pub fee: f64,
+    /// Rating the producer received
+    pub rating: f64,
+    /// Service category (e.g. "delivery")
+    pub category: String,
+    /// Set when the transaction was disputed
+    pub disputed: bool,
+    /// Currency code of the fee (e.g. "EUR")
+    pub currency: String,
+}
+
+/// Edge weight contribution from a caller-supplied function of the edge metadata
+///
+/// Negative and non-finite results are treated as 0, since EC needs w ≥ 0.
+pub fn edge_weight_with(meta: &EdgeMeta, weight: impl Fn(&EdgeMeta) -> f64) -> f64 {
+    let w = weight(meta);
+    if w.is_finite() {
+        w.max(0.0)
+    } else {
+        0.0
+    }
+}
+
+/// How to handle self-edges (w(u,u) ≠ 0) and all-zero rows (isolated nodes)
+///
+/// Self-edges inflate total_weight and EC without any counterparty.
+/// Isolated nodes have W = 0 and EC = 0, so their Graph Value is always 0.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EdgeCasePolicy {
+    /// Return an error
+    Reject,
+    /// Drop them (zero the self-edge / leave the node out)
+    Ignore,
+    /// Keep them as they are
+    Include,
+}
+
+/// Apply a self-edge policy to the adjacency matrix
+pub fn apply_self_loop_policy<const N: usize>(
+    weights: &[[f64; N]; N],
+    policy: EdgeCasePolicy,
+) -> Result<[[f64; N]; N], String> {
+    let mut result = *weights;
+    for (i, row) in result.iter_mut().enumerate() {
+        if row[i] != 0.0 {
+            match policy {
+                EdgeCasePolicy::Reject => return Err(format!("self-edge on node {}", i)),
+                EdgeCasePolicy::Ignore => row[i] = 0.0,
+                EdgeCasePolicy::Include => {}
+            }
+        }
+    }
+    Ok(result)
+}
+
+/// Apply an all-zero-row policy to a set of node indices (e.g. producer_indices)
+///
+/// Returns: the indices to score
+pub fn apply_zero_row_policy<const N: usize>(
+    weights: &[[f64; N]; N],
+    indices: &[usize],
+    policy: EdgeCasePolicy,
+) -> Result<Vec<usize>, String> {
+    let mut result = Vec::new();
+    for &i in indices {
+        if weights[i].iter().all(|&w| w == 0.0) {
+            match policy {
+                EdgeCasePolicy::Reject => return Err(format!("node {} has no edges", i)),
+                EdgeCasePolicy::Ignore => continue,
+                EdgeCasePolicy::Include => {}
+            }
+        }
+        result.push(i);
+    }
+    Ok(result)
+}
+
+/// How to combine A[i][j] and A[j][i] into one symmetric edge weight
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SymmetrizeMode {
+    Max,
+    Sum,
+    Avg,
+}
+
+/// Pairs (i, j), i < j, where A[i][j] ≠ A[j][i]
+pub fn asymmetric_pairs<const N: usize>(weights: &[[f64; N]; N]) -> Vec<(usize, usize)> {
+    let mut pairs = Vec::new();
+    for (i, row) in weights.iter().enumerate() {
+        for (j, &w) in row.iter().enumerate().skip(i + 1) {
+            if (w - weights[j][i]).abs() > 1e-12 {
+                pairs.push((i, j));
+            }
+        }
+    }
+    pairs
+}
+
+/// Make the adjacency matrix symmetric, as EC expects
+///
+/// Output: (symmetric matrix, asymmetric pairs that were combined), so the caller
+/// can report them
+pub fn symmetrize<const N: usize>(
+    weights: &[[f64; N]; N],
+    mode: SymmetrizeMode,
+) -> ([[f64; N]; N], Vec<(usize, usize)>) {
+    let mut result = *weights;
+    let pairs = asymmetric_pairs(weights);
+    for &(i, j) in &pairs {
+        let w = match mode {
+            SymmetrizeMode::Max => weights[i][j].max(weights[j][i]),
+            SymmetrizeMode::Sum => weights[i][j] + weights[j][i],
+            SymmetrizeMode::Avg => (weights[i][j] + weights[j][i]) / 2.0,
+        };
+        result[i][j] = w;
+        result[j][i] = w;
+    }
+    (result, pairs)
+}
+
+/// How multiple transactions between the same pair aggregate into one edge weight
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AggregationPolicy {
+    /// w = Σ fee
+    Sum,
+    /// w = Σ fee_k · decay^age_k, age_k = number of newer transactions on the edge
+    DecayedSum { decay: f64 },
+    /// w = min(Σ fee, cap)
+    CappedSum { cap: f64 },
+    /// w = number of transactions
+    Count,
+}
+
+/// Aggregate the fees of one edge (ordered oldest to newest) into an edge weight
+pub fn aggregate_fees(fees: &[f64], policy: AggregationPolicy) -> f64 {
+    match policy {
+        AggregationPolicy::Sum => fees.iter().sum(),
+        AggregationPolicy::DecayedSum { decay } => fees
+            .iter()
+            .rev()
+            .enumerate()
+            .map(|(age, fee)| fee * decay.powi(age as i32))
+            .sum(),
+        AggregationPolicy::CappedSum { cap } => fees.iter().sum::<f64>().min(cap),
+        AggregationPolicy::Count => fees.len() as f64,
+    }
+}
+
+/// Build a symmetric adjacency matrix from individual transactions
+///
+/// Input: (buyer, producer, fee) records, oldest first
+pub fn aggregate_edges<const N: usize>(
+    transactions: &[(usize, usize, f64)],
+    policy: AggregationPolicy,
+) -> [[f64; N]; N] {
+    let mut fees: Vec<Vec<Vec<f64>>> = vec![vec![Vec::new(); N]; N];
+    for &(buyer, producer, fee) in transactions {
+        let (a, b) = (buyer.min(producer), buyer.max(producer));
+        fees[a][b].push(fee);
+    }
+
+    let mut weights = [[0.0; N]; N];
+    for a in 0..N {
+        for b in a..N {
+            if !fees[a][b].is_empty() {
+                let w = aggregate_fees(&fees[a][b], policy);
+                weights[a][b] = w;
+                weights[b][a] = w;
+            }
+        }
+    }
+    weights
+}
+
+/// Build a symmetric adjacency matrix from transactions with metadata
+///
+/// Each transaction contributes edge_weight_with(meta, weight) in place of its fee,
+/// then contributions are aggregated per edge as in aggregate_edges.
+///
+/// Input: (buyer, producer, metadata) records, oldest first
+pub fn aggregate_edges_with<const N: usize>(
+    transactions: &[(usize, usize, EdgeMeta)],
+    policy: AggregationPolicy,
+    weight: impl Fn(&EdgeMeta) -> f64,
+) -> [[f64; N]; N] {
+    let weighted: Vec<(usize, usize, f64)> = transactions
+        .iter()
+        .map(|(buyer, producer, meta)| (*buyer, *producer, edge_weight_with(meta, &weight)))
+        .collect();
+    aggregate_edges(&weighted, policy)
+}
+
+/// Sample the ego network of a node
+///
+/// Breadth-first search up to `depth` hops, visiting heavier edges first,
+/// stopping once `max_nodes` nodes are collected.
+///
+/// Output: node indices, starting with `node`; empty if max_nodes is 0
+pub fn sample_neighborhood<const N: usize>(
+    weights: &[[f64; N]; N],
+    node: usize,
+    depth: usize,
+    max_nodes: usize,
+) -> Vec<usize> {
+    if max_nodes == 0 {
+        return Vec::new();
+    }
+
+    let mut visited = vec![false; N];
+    let mut sample = vec![node];
+    visited[node] = true;
+    let mut frontier = vec![node];
+
+    for _ in 0..depth {
+        let mut next = Vec::new();
+        for &u in &frontier {
+            let mut neighbors: Vec<usize> = (0..N)
+                .filter(|&v| !visited[v] && weights[u][v] > 0.0)
+                .collect();
+            neighbors.sort_by(|&a, &b| weights[u][b].total_cmp(&weights[u][a]));
+
+            for v in neighbors {
+                if sample.len() >= max_nodes {
+                    return sample;
+                }
+                visited[v] = true;
+                sample.push(v);
+                next.push(v);
+            }
+        }
+        frontier = next;
+    }
+
+    sample
+}
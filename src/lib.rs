@@ -0,0 +1,35 @@
+//! Marketplace scoring engine
+//!
+//! Transaction → EC Score → Graph Value → Rewards
+//!
+//! - ec: Eigenvector Centrality (power iteration on A²) and fallbacks
+//! - graph: Graph Value GV = W^x̄ · x^(1-x̄) · r, graph types, reward shares, α_u
+//! - reputation: reputation updates from peer reviews
+//! - marketplace: Marketplace state owning the graph, reputations and tx counts
+//! - transaction: append-only transaction log with deterministic replay
+//! - engine: ScoringEngine trait over Marketplace and a deterministic stub
+//!
+//! The remaining modules build on these: analysis, backtest, bands, complaints,
+//! embeddings, governance, motifs, privacy, roles and viz.
+
+pub mod analysis;
+pub mod backtest;
+pub mod bands;
+pub mod complaints;
+pub mod ec;
+pub mod embeddings;
+pub mod engine;
+pub mod governance;
+pub mod graph;
+pub mod marketplace;
+pub mod motifs;
+pub mod privacy;
+pub mod reputation;
+pub mod roles;
+pub mod transaction;
+pub mod viz;
+
+pub use engine::{ScoringEngine, StubEngine};
+pub use graph::{Graph, SparseGraph};
+pub use marketplace::Marketplace;
+pub use transaction::{Transaction, TransactionLog};
@@ -1,6 +1,4 @@
-mod ec;
-mod graph;
-mod reputation;
+use marketplace::{ec, graph, reputation};
 
 fn main() {
     // Peers 0 and 1 have higher weights which results in higher graph values
@@ -108,6 +106,10 @@ fn main() {
         old_gv,
         compute_gv(producer, &reputations)
     );
+    println!(
+        "producer {} transactions: {}",
+        producer, tx_counts[producer]
+    );
 }
 
 // EXECUTION LOGS: ------------------------------------------------------------
@@ -134,3 +136,4 @@ fn main() {
 // buyer 1 graph value: 2.450000
 // reputation: 0.111649 -> 4.157766 (Δ = 4.046117) <--- Big change in reputation of peer 4
 // graph value: 0.016742 -> 0.623482
+// producer 4 transactions: 3
@@ -0,0 +1,355 @@
+//! Marketplace State
+//!
+//! Owns the transaction graph, per-node reputations and transaction counts, and
+//! keeps them consistent when a transaction is recorded.
+//!
+//! Recording a transaction between buyer v and producer u:
+//! - Convert the fee by the marketplace's EdgeWeighting (service hours blend,
+//!   quality adjustment), store it with the edge's earlier contributions and
+//!   recompute w(u,v) from them with the aggregation policy
+//! - Compute EC on the updated graph, then G_u and G_v
+//! - Mutual review: r_u updated with G_v and the buyer's rating, r_v with G_u
+//!   and the producer's rating (see reputation::mutual_update)
+//! - Increment N_u and N_v
+//!
+//! The fee is added first so a first transaction has W > 0 and non-zero reviewer
+//! graph values. Both graph values are taken before reputations change, so the
+//! update does not depend on the order of the two reviews.
+
+use crate::ec;
+use crate::graph::{self, AggregationPolicy, Graph, WeightMode};
+use crate::reputation;
+use crate::transaction::{Transaction, TransactionLog};
+use std::collections::BTreeMap;
+
+/// How a transaction becomes an edge weight contribution
+///
+/// w_k = edge_weight(service_weight(fee, hours, hour_value, blend), rating, mode)
+/// w(u,v) = aggregate_fees([w_1, ..., w_k], aggregation)
+///
+/// The default (Fee mode, blend 0, Sum) adds the plain fee.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct EdgeWeighting {
+    pub mode: WeightMode,
+    /// Fee-equivalent value of one service hour
+    pub hour_value: f64,
+    /// 0 uses only the fee, 1 uses only the service hours
+    pub blend: f64,
+    /// How the contributions of repeated transactions on one edge combine
+    pub aggregation: AggregationPolicy,
+}
+
+impl Default for EdgeWeighting {
+    fn default() -> Self {
+        EdgeWeighting {
+            mode: WeightMode::Fee,
+            hour_value: 0.0,
+            blend: 0.0,
+            aggregation: AggregationPolicy::Sum,
+        }
+    }
+}
+
+/// An audited configuration change
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConfigChange {
+    /// Who made the change (e.g. governance proposal or operator id)
+    pub who: String,
+    /// Unix timestamp in seconds
+    pub when: u64,
+    pub old: EdgeWeighting,
+    pub new: EdgeWeighting,
+}
+
+/// Marketplace state: graph, reputations and transaction counts
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Marketplace {
+    graph: Graph,
+    reputations: Vec<f64>,
+    tx_counts: Vec<u64>,
+    weighting: EdgeWeighting,
+    config_changes: Vec<ConfigChange>,
+    /// Weight contributions per edge (u < v), oldest first
+    edge_contributions: BTreeMap<(usize, usize), Vec<f64>>,
+}
+
+impl Marketplace {
+    /// Create a marketplace with `users` users, no edges and reputation R_MIN
+    pub fn new(users: usize) -> Self {
+        Marketplace {
+            graph: Graph::new(users),
+            reputations: vec![reputation::R_MIN; users],
+            tx_counts: vec![0; users],
+            weighting: EdgeWeighting::default(),
+            config_changes: Vec::new(),
+            edge_contributions: BTreeMap::new(),
+        }
+    }
+
+    /// Create a marketplace from an existing graph, with reputation R_MIN
+    pub fn from_graph(graph: Graph) -> Self {
+        let users = graph.node_count();
+        Marketplace {
+            graph,
+            reputations: vec![reputation::R_MIN; users],
+            tx_counts: vec![0; users],
+            weighting: EdgeWeighting::default(),
+            config_changes: Vec::new(),
+            edge_contributions: BTreeMap::new(),
+        }
+    }
+
+    /// Add a user with reputation R_MIN, returning its index
+    pub fn add_user(&mut self) -> usize {
+        self.reputations.push(reputation::R_MIN);
+        self.tx_counts.push(0);
+        self.graph.add_node()
+    }
+
+    /// Set how fees and service hours of new transactions become edge weights
+    ///
+    /// Existing edges are re-aggregated from their stored contributions.
+    pub fn set_weighting(&mut self, weighting: EdgeWeighting) {
+        self.weighting = weighting;
+        self.reaggregate();
+    }
+
+    fn reaggregate(&mut self) {
+        for (&(u, v), contributions) in &self.edge_contributions {
+            let w = graph::aggregate_fees(contributions, self.weighting.aggregation);
+            self.graph.remove_edge(u, v);
+            self.graph.add_edge(u, v, w);
+        }
+    }
+
+    /// Edge weight settings
+    pub fn weighting(&self) -> EdgeWeighting {
+        self.weighting
+    }
+
+    /// Validate and apply new edge weight settings, recording the change
+    ///
+    /// Applies immediately, to transactions recorded from now on.
+    ///
+    /// Returns: Err if blend is outside [0, 1], hour_value is negative or not finite,
+    /// or the aggregation decay is outside [0, 1] or its cap is negative
+    pub fn update_config(
+        &mut self,
+        weighting: EdgeWeighting,
+        who: &str,
+        when: u64,
+    ) -> Result<(), String> {
+        if !(0.0..=1.0).contains(&weighting.blend) {
+            return Err(format!("blend {} is outside [0, 1]", weighting.blend));
+        }
+        if !(weighting.hour_value.is_finite() && weighting.hour_value >= 0.0) {
+            return Err(format!(
+                "hour_value {} must be finite and ≥ 0",
+                weighting.hour_value
+            ));
+        }
+
+        match weighting.aggregation {
+            AggregationPolicy::DecayedSum { decay } if !(0.0..=1.0).contains(&decay) => {
+                return Err(format!("decay {} is outside [0, 1]", decay));
+            }
+            AggregationPolicy::CappedSum { cap } if cap.is_nan() || cap < 0.0 => {
+                return Err(format!("cap {} must be ≥ 0", cap));
+            }
+            _ => {}
+        }
+
+        self.config_changes.push(ConfigChange {
+            who: who.to_string(),
+            when,
+            old: self.weighting,
+            new: weighting,
+        });
+        self.set_weighting(weighting);
+        Ok(())
+    }
+
+    /// Audit log of update_config changes, oldest first
+    pub fn config_changes(&self) -> &[ConfigChange] {
+        &self.config_changes
+    }
+
+    /// Record a transaction and the mutual reviews that follow it
+    ///
+    /// Arguments:
+    /// - buyer, producer: the two parties
+    /// - fee: transaction fee, converted by the edge weighting and added to the edge weight
+    /// - buyer_rating: rating the buyer gives the producer
+    /// - producer_rating: rating the producer gives the buyer
+    ///
+    /// Returns: Err if buyer and producer are the same user (a self-review)
+    pub fn record_transaction(
+        &mut self,
+        buyer: usize,
+        producer: usize,
+        fee: f64,
+        buyer_rating: f64,
+        producer_rating: f64,
+    ) -> Result<(), String> {
+        let weight = graph::edge_weight(fee, buyer_rating, self.weighting.mode);
+        self.apply(buyer, producer, weight, buyer_rating, producer_rating, None)
+    }
+
+    /// Record a logged transaction, blending its service hours into the edge weight
+    ///
+    /// If the buyer's review was submitted by a delegate, it is weighted with the
+    /// buyer's graph value attenuated by delegate_attenuation
+    /// (see reputation::update_reputation_delegated).
+    ///
+    /// Returns: Err if buyer and producer are the same user (a self-review), or the
+    /// producer is the buyer's delegate
+    pub fn record(&mut self, transaction: &Transaction) -> Result<(), String> {
+        if transaction.delegate == Some(transaction.producer) {
+            return Err(format!(
+                "producer {} cannot review itself as the buyer's delegate",
+                transaction.producer
+            ));
+        }
+        let fee = graph::service_weight(
+            transaction.fee,
+            transaction.duration_hours,
+            self.weighting.hour_value,
+            self.weighting.blend,
+        );
+        let weight = graph::edge_weight(fee, transaction.buyer_rating, self.weighting.mode);
+        self.apply(
+            transaction.buyer,
+            transaction.producer,
+            weight,
+            transaction.buyer_rating,
+            transaction.producer_rating,
+            transaction
+                .delegate
+                .map(|_| transaction.delegate_attenuation),
+        )
+    }
+
+    /// delegate_attenuation: Some(attenuation) if the buyer's review was delegated
+    fn apply(
+        &mut self,
+        buyer: usize,
+        producer: usize,
+        weight: f64,
+        buyer_rating: f64,
+        producer_rating: f64,
+        delegate_attenuation: Option<f64>,
+    ) -> Result<(), String> {
+        if buyer == producer {
+            return Err(format!("user {} cannot transact with itself", buyer));
+        }
+
+        // Edges from from_graph have no stored contributions: keep their weight as the first
+        let key = (buyer.min(producer), buyer.max(producer));
+        let existing = self.graph.weight(buyer, producer);
+        let contributions = self.edge_contributions.entry(key).or_insert_with(|| {
+            if existing != 0.0 {
+                vec![existing]
+            } else {
+                Vec::new()
+            }
+        });
+        contributions.push(weight);
+        let w = graph::aggregate_fees(contributions, self.weighting.aggregation);
+        self.graph.remove_edge(buyer, producer);
+        self.graph.add_edge(buyer, producer, w);
+
+        let ec = ec::graph_power_iteration(&self.graph);
+        let normalized_ec = ec::normalize_ec_slice(&ec);
+        let buyer_gv = self.graph_value_with(buyer, &ec, &normalized_ec);
+        let producer_gv = self.graph_value_with(producer, &ec, &normalized_ec);
+
+        let (mut producer_rep, buyer_rep) = reputation::mutual_update(
+            self.reputations[producer],
+            self.tx_counts[producer],
+            producer_gv,
+            producer_rating,
+            self.reputations[buyer],
+            self.tx_counts[buyer],
+            buyer_gv,
+            buyer_rating,
+        );
+        if let Some(attenuation) = delegate_attenuation {
+            producer_rep = reputation::update_reputation_delegated(
+                self.reputations[producer],
+                self.tx_counts[producer],
+                buyer_gv,
+                attenuation,
+                buyer_rating,
+            );
+        }
+
+        self.reputations[producer] = producer_rep;
+        self.reputations[buyer] = buyer_rep;
+        self.tx_counts[producer] += 1;
+        self.tx_counts[buyer] += 1;
+        Ok(())
+    }
+
+    /// Marketplace state (reputations, graph and therefore graph values) as of a past time
+    ///
+    /// Replays from the log's nearest checkpoint at or before `timestamp`.
+    ///
+    /// Returns: Err if `timestamp` is before the log's compaction point
+    pub fn as_of(log: &TransactionLog, timestamp: u64) -> Result<Marketplace, String> {
+        log.replay_until(timestamp)
+    }
+
+    /// Current Graph Value of a user
+    pub fn graph_value(&self, user: usize) -> f64 {
+        let ec = ec::graph_power_iteration(&self.graph);
+        let normalized_ec = ec::normalize_ec_slice(&ec);
+        self.graph_value_with(user, &ec, &normalized_ec)
+    }
+
+    /// Current Graph Values of the given producers
+    pub fn graph_values(&self, producer_indices: &[usize]) -> Vec<(usize, f64)> {
+        let ec = ec::graph_power_iteration(&self.graph);
+        let normalized_ec = ec::normalize_ec_slice(&ec);
+        graph::dynamic_graph_values(
+            &self.graph,
+            &ec,
+            &normalized_ec,
+            &self.reputations,
+            producer_indices,
+        )
+    }
+
+    fn graph_value_with(&self, user: usize, ec: &[f64], normalized_ec: &[f64]) -> f64 {
+        graph::graph_value(
+            self.graph.total_weight(user),
+            normalized_ec[user],
+            ec[user],
+            self.reputations[user],
+        )
+    }
+
+    /// The transaction graph
+    pub fn graph(&self) -> &Graph {
+        &self.graph
+    }
+
+    /// Reputation of a user
+    pub fn reputation(&self, user: usize) -> f64 {
+        self.reputations[user]
+    }
+
+    /// Reputations of all users
+    pub fn reputations(&self) -> &[f64] {
+        &self.reputations
+    }
+
+    /// Number of users
+    pub fn user_count(&self) -> usize {
+        self.reputations.len()
+    }
+
+    /// Number of transactions a user has completed
+    pub fn tx_count(&self, user: usize) -> u64 {
+        self.tx_counts[user]
+    }
+}
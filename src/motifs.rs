@@ -0,0 +1,80 @@
+//! Temporal Motifs
+//!
+//! Detects bursts of positive reviews over the timestamped transaction log:
+//! a producer receiving ≥ min_reviews ratings ≥ min_rating within window_seconds.
+//!
+//! Overlapping windows of the same producer are merged into one flag covering
+//! the whole burst. Rules on reviewer accounts (e.g. accounts created the same
+//! week) need account creation times, which the log does not record.
+
+use crate::transaction::Transaction;
+
+/// A burst rule
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BurstRule {
+    pub min_reviews: usize,
+    /// Only buyer ratings ≥ min_rating count
+    pub min_rating: f64,
+    pub window_seconds: u64,
+}
+
+/// A producer flagged by a burst rule
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BurstFlag {
+    pub producer: usize,
+    /// Timestamps of the first and last review in the burst
+    pub start: u64,
+    pub end: u64,
+    /// Qualifying reviews between start and end
+    pub reviews: usize,
+}
+
+/// Flag every burst matching the rule
+///
+/// Input: transactions ordered by timestamp (e.g. TransactionLog::transactions)
+///
+/// Output: flags ordered by producer, then start time
+pub fn detect_bursts(transactions: &[Transaction], rule: BurstRule) -> Vec<BurstFlag> {
+    let mut by_producer: Vec<(usize, u64)> = transactions
+        .iter()
+        .filter(|tx| tx.buyer_rating >= rule.min_rating)
+        .map(|tx| (tx.producer, tx.timestamp))
+        .collect();
+    by_producer.sort();
+
+    let min_reviews = rule.min_reviews.max(1);
+    let mut flags: Vec<BurstFlag> = Vec::new();
+    // Index of the first review of the last flag
+    let mut burst_first = 0;
+    let mut first = 0;
+    for j in 0..by_producer.len() {
+        let (producer, t) = by_producer[j];
+        if j > 0 && by_producer[j - 1].0 != producer {
+            first = j;
+        }
+        // Sliding window [first, j] of one producer spanning at most window_seconds
+        while t - by_producer[first].1 > rule.window_seconds {
+            first += 1;
+        }
+        if j + 1 - first < min_reviews {
+            continue;
+        }
+
+        match flags.last_mut() {
+            Some(flag) if flag.producer == producer && flag.end >= by_producer[first].1 => {
+                flag.end = t;
+                flag.reviews = j + 1 - burst_first;
+            }
+            _ => {
+                burst_first = first;
+                flags.push(BurstFlag {
+                    producer,
+                    start: by_producer[first].1,
+                    end: t,
+                    reviews: j + 1 - first,
+                });
+            }
+        }
+    }
+    flags
+}
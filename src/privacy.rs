@@ -0,0 +1,137 @@
+//! Privacy for Published Scores
+//!
+//! Applied to leaderboards and reports before publication; internal scores are
+//! left untouched.
+//!
+//! - k-anonymity: entries from cohorts with fewer than k members are suppressed,
+//!   so a small cohort's stats cannot be traced back to individual transactions
+//! - Laplace noise: score + Lap(b), b = sensitivity / ε, drawn by inverse CDF from
+//!   a caller-supplied uniform source (so callers choose the RNG and seeding)
+//! - Pseudonyms: node ids are replaced by hex(SHA-256(salt || id)); the same salt
+//!   gives the same pseudonym in every exported file (graph, log, reports)
+
+use crate::transaction::{sha256, Transaction};
+
+/// Drop leaderboard entries whose cohort has fewer than k entries
+///
+/// Input: cohorts = cohort index per entry of leaderboard
+///
+/// Returns: Err if cohorts and leaderboard differ in length
+pub fn suppress_small_cohorts(
+    leaderboard: &[(usize, f64)],
+    cohorts: &[usize],
+    k: usize,
+) -> Result<Vec<(usize, f64)>, String> {
+    if cohorts.len() != leaderboard.len() {
+        return Err(format!(
+            "{} cohorts for {} leaderboard entries",
+            cohorts.len(),
+            leaderboard.len()
+        ));
+    }
+
+    let size = |c: usize| cohorts.iter().filter(|&&other| other == c).count();
+    Ok(leaderboard
+        .iter()
+        .zip(cohorts)
+        .filter(|(_, &c)| size(c) >= k)
+        .map(|(&entry, _)| entry)
+        .collect())
+}
+
+/// Add Laplace noise calibrated to ε to every score
+///
+/// noise = -b · sign(u) · ln(1 - 2|u|), u = uniform() - 0.5, b = sensitivity / ε
+///
+/// Input:
+/// - sensitivity: most one user's transactions can change a score
+/// - epsilon: privacy budget, smaller = noisier
+/// - uniform: source of samples in [0, 1)
+///
+/// Returns: Err if epsilon or sensitivity is not positive
+pub fn laplace_noise(
+    leaderboard: &[(usize, f64)],
+    sensitivity: f64,
+    epsilon: f64,
+    mut uniform: impl FnMut() -> f64,
+) -> Result<Vec<(usize, f64)>, String> {
+    if !(epsilon > 0.0 && sensitivity > 0.0) {
+        return Err("epsilon and sensitivity must be positive".to_string());
+    }
+
+    let b = sensitivity / epsilon;
+    Ok(leaderboard
+        .iter()
+        .map(|&(i, score)| {
+            let u = uniform().clamp(0.0, 1.0 - f64::EPSILON) - 0.5;
+            let noise = -b * u.signum() * (1.0 - 2.0 * u.abs()).ln();
+            (i, score + noise)
+        })
+        .collect())
+}
+
+/// Salted-hash pseudonym of a node id
+pub fn pseudonym(user: usize, salt: &[u8]) -> String {
+    let mut input = salt.to_vec();
+    input.extend((user as u64).to_le_bytes());
+    sha256(&input)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Scores (leaderboards, reports) keyed by pseudonym
+pub fn pseudonymize_scores(scores: &[(usize, f64)], salt: &[u8]) -> Vec<(String, f64)> {
+    scores
+        .iter()
+        .map(|&(i, score)| (pseudonym(i, salt), score))
+        .collect()
+}
+
+/// Graph edges (u, v, w) keyed by pseudonym
+pub fn pseudonymize_edges(
+    edges: &[(usize, usize, f64)],
+    salt: &[u8],
+) -> Vec<(String, String, f64)> {
+    edges
+        .iter()
+        .map(|&(u, v, w)| (pseudonym(u, salt), pseudonym(v, salt), w))
+        .collect()
+}
+
+/// A transaction with pseudonyms in place of node ids
+#[derive(Clone, Debug, PartialEq)]
+pub struct PseudonymousTransaction {
+    pub buyer: String,
+    pub producer: String,
+    pub fee: f64,
+    pub duration_hours: Option<f64>,
+    pub region: Option<u32>,
+    pub buyer_rating: f64,
+    pub delegate: Option<String>,
+    pub delegate_attenuation: f64,
+    pub producer_rating: f64,
+    pub timestamp: u64,
+}
+
+/// Transaction log entries keyed by pseudonym
+pub fn pseudonymize_transactions(
+    transactions: &[Transaction],
+    salt: &[u8],
+) -> Vec<PseudonymousTransaction> {
+    transactions
+        .iter()
+        .map(|tx| PseudonymousTransaction {
+            buyer: pseudonym(tx.buyer, salt),
+            producer: pseudonym(tx.producer, salt),
+            fee: tx.fee,
+            duration_hours: tx.duration_hours,
+            region: tx.region,
+            buyer_rating: tx.buyer_rating,
+            delegate: tx.delegate.map(|d| pseudonym(d, salt)),
+            delegate_attenuation: tx.delegate_attenuation,
+            producer_rating: tx.producer_rating,
+            timestamp: tx.timestamp,
+        })
+        .collect()
+}
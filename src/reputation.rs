@@ -1,19 +1,19 @@
-/// Reputation Score
-///
-/// From the paper, Section 6.4:
-/// - Users start with a minimum reputation r_min (not zero, to avoid zeroing graph values)
-/// - After each transaction, buyer and producer mutually review each other
-/// - Reviews are weighted by the reviewer's Graph Value
-///
-/// Update formula for producer u after transacting with buyer v:
-///   r_u = (N_u * r_u + G_v * r_vu) / (N_u + 1)
-///
-/// Where:
-/// - N_u = number of transactions producer has completed before this one
-/// - G_v = graph value of the buyer giving the review
-/// - r_vu = the rating buyer v gives to producer u (in range r_min to r_max)
-///
-/// Key insight: reviews from high graph-value users have more impact
+//! Reputation Score
+//!
+//! From the paper, Section 6.4:
+//! - Users start with a minimum reputation r_min (not zero, to avoid zeroing graph values)
+//! - After each transaction, buyer and producer mutually review each other
+//! - Reviews are weighted by the reviewer's Graph Value
+//!
+//! Update formula for producer u after transacting with buyer v:
+//!   r_u = (N_u * r_u + G_v * r_vu) / (N_u + 1)
+//!
+//! Where:
+//! - N_u = number of transactions producer has completed before this one
+//! - G_v = graph value of the buyer giving the review
+//! - r_vu = the rating buyer v gives to producer u (in range r_min to r_max)
+//!
+//! Key insight: reviews from high graph-value users have more impact
 
 /// Default minimum reputation (must be > 0 to avoid zeroing graph values)
 pub const R_MIN: f64 = 0.1;
@@ -60,6 +60,7 @@ pub fn update_reputation(
 /// Both producer and buyer update each other's reputation
 ///
 /// Returns: (new_producer_reputation, new_buyer_reputation)
+#[allow(clippy::too_many_arguments)]
 pub fn mutual_update(
     producer_rep: f64,
     producer_tx_count: u64,
@@ -86,3 +87,197 @@ pub fn mutual_update(
 
     (new_producer_rep, new_buyer_rep)
 }
+
+/// Map a rating from an external scale onto [R_MIN, R_MAX]
+///
+/// r = R_MIN + (rating - ext_min) / (ext_max - ext_min) · (R_MAX - R_MIN)
+pub fn map_external_rating(rating: f64, ext_min: f64, ext_max: f64) -> f64 {
+    let range = ext_max - ext_min;
+    if range.abs() < 1e-15 {
+        return R_MIN;
+    }
+    clamp_rating(R_MIN + (rating - ext_min) / range * (R_MAX - R_MIN))
+}
+
+/// Import initial reputations from an external review system
+///
+/// Input: CSV with lines `user,avg_rating,count`, ratings on the scale ext_min..ext_max.
+/// Empty lines and a leading `user,...` header are skipped.
+///
+/// Returns: (user, reputation, tx_count) for each line
+pub fn import_reputations(
+    csv: &str,
+    ext_min: f64,
+    ext_max: f64,
+) -> Result<Vec<(usize, f64, u64)>, String> {
+    let mut imported = Vec::new();
+
+    for (line_no, line) in csv.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || (line_no == 0 && line.starts_with("user")) {
+            continue;
+        }
+
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        if fields.len() != 3 {
+            return Err(format!("line {}: expected 3 fields", line_no + 1));
+        }
+
+        let user = fields[0]
+            .parse::<usize>()
+            .map_err(|e| format!("line {}: user: {}", line_no + 1, e))?;
+        let avg_rating = fields[1]
+            .parse::<f64>()
+            .map_err(|e| format!("line {}: avg rating: {}", line_no + 1, e))?;
+        let count = fields[2]
+            .parse::<u64>()
+            .map_err(|e| format!("line {}: count: {}", line_no + 1, e))?;
+
+        imported.push((
+            user,
+            map_external_rating(avg_rating, ext_min, ext_max),
+            count,
+        ));
+    }
+
+    Ok(imported)
+}
+
+/// Update reputation from a review submitted by a delegate on behalf of an account
+///
+/// The effective reviewer graph value is the delegator's, attenuated:
+///   G_eff = attenuation · G_delegator   (attenuation in 0..1)
+///
+/// Returns: new reputation score
+pub fn update_reputation_delegated(
+    current_reputation: f64,
+    num_transactions: u64,
+    delegator_graph_value: f64,
+    attenuation: f64,
+    rating: f64,
+) -> f64 {
+    let effective_graph_value = attenuation.clamp(0.0, 1.0) * delegator_graph_value;
+    update_reputation(
+        current_reputation,
+        num_transactions,
+        effective_graph_value,
+        rating,
+    )
+}
+
+/// Attenuate a reviewer's graph value by their trust tier (k-core number)
+///
+/// Reviewers with core number below min_core count with attenuation · G_v
+/// instead of G_v, so peripheral throwaway accounts carry less influence.
+pub fn core_attenuated_graph_value(
+    reviewer_graph_value: f64,
+    reviewer_core: f64,
+    min_core: f64,
+    attenuation: f64,
+) -> f64 {
+    if reviewer_core >= min_core {
+        reviewer_graph_value
+    } else {
+        attenuation.clamp(0.0, 1.0) * reviewer_graph_value
+    }
+}
+
+/// Update reputation, ignoring or attenuating reviews from reviewers below a minimum graph value
+///
+/// A normal update moves r_u to r_u' = update_reputation(...). For a reviewer with
+/// G_v < min_graph_value the move is scaled down:
+///   r_u'' = r_u + attenuation · (r_u' - r_u)
+///
+/// attenuation = 0 gives the review zero weight (reputation unchanged), so armies of
+/// near-zero-GV accounts cannot drag a producer down. The review still counts as a
+/// transaction for the caller's tx count.
+pub fn update_reputation_with_floor(
+    current_reputation: f64,
+    num_transactions: u64,
+    reviewer_graph_value: f64,
+    rating: f64,
+    min_graph_value: f64,
+    attenuation: f64,
+) -> f64 {
+    let updated = update_reputation(
+        current_reputation,
+        num_transactions,
+        reviewer_graph_value,
+        rating,
+    );
+    if reviewer_graph_value >= min_graph_value {
+        return updated;
+    }
+    current_reputation + attenuation.clamp(0.0, 1.0) * (updated - current_reputation)
+}
+
+/// Attenuate a reviewer's graph value by their dispute/refund history
+///
+/// G_eff = G_v · (1 - disputed / total)
+///
+/// Buyers whose transactions are frequently disputed or refunded count for less.
+pub fn dispute_attenuated_graph_value(
+    reviewer_graph_value: f64,
+    disputed_transactions: u64,
+    total_transactions: u64,
+) -> f64 {
+    if total_transactions == 0 {
+        return reviewer_graph_value;
+    }
+    let dispute_rate = (disputed_transactions as f64 / total_transactions as f64).min(1.0);
+    reviewer_graph_value * (1.0 - dispute_rate)
+}
+
+/// Operator override of a user's trust
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TrustOverride {
+    /// Reviews by this user are ignored
+    ZeroWeight,
+    /// Reviews by this user use its raw graph value, skipping attenuation
+    FullWeight,
+    /// The user's reputation is fixed at this value
+    ManualReputation(f64),
+}
+
+/// Override configured for a user, if any (the last entry wins)
+pub fn override_for(overrides: &[(usize, TrustOverride)], user: usize) -> Option<TrustOverride> {
+    overrides
+        .iter()
+        .rev()
+        .find(|&&(u, _)| u == user)
+        .map(|&(_, o)| o)
+}
+
+/// Update reputation, applying the reviewer's override
+///
+/// - ZeroWeight: the review is ignored, reputation unchanged
+/// - FullWeight: weighted by the raw reviewer graph value
+/// - otherwise: weighted by the effective (attenuated) reviewer graph value
+pub fn update_reputation_with_override(
+    current_reputation: f64,
+    num_transactions: u64,
+    raw_graph_value: f64,
+    effective_graph_value: f64,
+    rating: f64,
+    reviewer_override: Option<TrustOverride>,
+) -> f64 {
+    let reviewer_graph_value = match reviewer_override {
+        Some(TrustOverride::ZeroWeight) => return current_reputation,
+        Some(TrustOverride::FullWeight) => raw_graph_value,
+        _ => effective_graph_value,
+    };
+    update_reputation(
+        current_reputation,
+        num_transactions,
+        reviewer_graph_value,
+        rating,
+    )
+}
+
+/// Reputation after the user's own override: the manual value if one is set
+pub fn overridden_reputation(computed: f64, user_override: Option<TrustOverride>) -> f64 {
+    match user_override {
+        Some(TrustOverride::ManualReputation(r)) => r,
+        _ => computed,
+    }
+}
@@ -0,0 +1,111 @@
+//! Node Roles
+//!
+//! Beyond buyers and producers, a marketplace can have other participants
+//! that are scored, e.g. couriers and arbiters.
+//!
+//! - Each node has a role; graph_values can be restricted to one role via role_indices,
+//!   and role_ranking ranks the Graph Values of one role
+//! - Each role keeps its own reputation track (one reputation array per role), so a
+//!   user's courier reviews do not mix with its buyer reviews
+//! - Reviews are only allowed between configured (reviewer, reviewee) role pairs
+
+use crate::reputation;
+
+/// Role of a node in the marketplace
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Role {
+    Buyer,
+    Producer,
+    Courier,
+    Arbiter,
+}
+
+/// Default review pairs: buyers and producers review each other
+pub const DEFAULT_REVIEW_PAIRS: [(Role, Role); 2] =
+    [(Role::Buyer, Role::Producer), (Role::Producer, Role::Buyer)];
+
+/// Indices of all nodes with the given role (e.g. producer_indices for graph_values)
+pub fn role_indices(roles: &[Role], role: Role) -> Vec<usize> {
+    roles
+        .iter()
+        .enumerate()
+        .filter(|(_, &r)| r == role)
+        .map(|(i, _)| i)
+        .collect()
+}
+
+/// Check whether a node with role `reviewer` may review a node with role `reviewee`
+pub fn can_review(allowed_pairs: &[(Role, Role)], reviewer: Role, reviewee: Role) -> bool {
+    allowed_pairs.contains(&(reviewer, reviewee))
+}
+
+/// Graph Values of the nodes with the given role, highest first
+pub fn role_ranking(gvs: &[(usize, f64)], roles: &[Role], role: Role) -> Vec<(usize, f64)> {
+    let mut ranking: Vec<(usize, f64)> = gvs
+        .iter()
+        .copied()
+        .filter(|&(i, _)| roles[i] == role)
+        .collect();
+    ranking.sort_by(|a, b| b.1.total_cmp(&a.1));
+    ranking
+}
+
+/// One reputation track (reputations and transaction counts) per role
+#[derive(Clone, Debug, PartialEq)]
+pub struct RoleReputations {
+    reputations: [Vec<f64>; 4],
+    tx_counts: [Vec<u64>; 4],
+}
+
+impl RoleReputations {
+    /// Create tracks for `users` users, every reputation R_MIN
+    pub fn new(users: usize) -> Self {
+        RoleReputations {
+            reputations: std::array::from_fn(|_| vec![reputation::R_MIN; users]),
+            tx_counts: std::array::from_fn(|_| vec![0; users]),
+        }
+    }
+
+    /// Reputation of a user in the given role
+    pub fn reputation(&self, role: Role, user: usize) -> f64 {
+        self.reputations[role as usize][user]
+    }
+
+    /// Reviews a user has received in the given role
+    pub fn tx_count(&self, role: Role, user: usize) -> u64 {
+        self.tx_counts[role as usize][user]
+    }
+
+    /// Apply a review to the reviewee's track for its role
+    ///
+    /// r = (N·r + G_reviewer · rating) / (N + 1), see reputation::update_reputation
+    ///
+    /// Returns: the new reputation, or Err if the role pair may not review
+    pub fn review(
+        &mut self,
+        allowed_pairs: &[(Role, Role)],
+        reviewer_role: Role,
+        reviewer_graph_value: f64,
+        reviewee: usize,
+        reviewee_role: Role,
+        rating: f64,
+    ) -> Result<f64, String> {
+        if !can_review(allowed_pairs, reviewer_role, reviewee_role) {
+            return Err(format!(
+                "{:?} may not review {:?}",
+                reviewer_role, reviewee_role
+            ));
+        }
+
+        let track = reviewee_role as usize;
+        let updated = reputation::update_reputation(
+            self.reputations[track][reviewee],
+            self.tx_counts[track][reviewee],
+            reviewer_graph_value,
+            rating,
+        );
+        self.reputations[track][reviewee] = updated;
+        self.tx_counts[track][reviewee] += 1;
+        Ok(updated)
+    }
+}
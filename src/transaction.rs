@@ -0,0 +1,480 @@
+//! Transaction Log
+//!
+//! Every transaction is appended to a log, in timestamp order. Replaying the log
+//! from genesis (an empty marketplace, or one with custom edge weighting)
+//! deterministically reconstructs edge weights, reputations, transaction counts
+//! and therefore graph values, so state can be audited and recovered instead of
+//! existing only in memory.
+//!
+//! Retention: transactions older than the retention window are compacted into a
+//! base state (aggregated edge weights and final reputations/tx counts). Replay then
+//! starts from the base instead of genesis, giving the same result as a full replay.
+//!
+//! Hash chain: each appended transaction commits the previous head hash plus the
+//! transaction, h_i = SHA-256(h_{i-1} || tx_i) with h_0 = 0. The head is not
+//! changed by compaction, so replicas that appended the same transactions have the
+//! same head_hash() regardless of when they compacted.
+//!
+//! Checkpoints: every checkpoint_interval appended transactions the replayed state
+//! is kept, so replay, replay_until (as-of queries) and append only replay from the
+//! nearest checkpoint instead of from the base.
+//!
+//! Deletion: forget drops a user's uncompacted transactions and re-chains the head
+//! from the compaction point, so the head then commits the edited history.
+
+use crate::marketplace::{EdgeWeighting, Marketplace};
+use std::ops::Range;
+
+/// Transactions between checkpoints for logs created with new/from_genesis
+pub const DEFAULT_CHECKPOINT_INTERVAL: usize = 100;
+
+/// A completed transaction and the mutual reviews that followed it
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Transaction {
+    pub buyer: usize,
+    pub producer: usize,
+    pub fee: f64,
+    /// Service hours, blended into the edge weight per the marketplace's EdgeWeighting
+    pub duration_hours: Option<f64>,
+    /// Region the transaction took place in
+    pub region: Option<u32>,
+    /// Rating the buyer gives the producer
+    pub buyer_rating: f64,
+    /// Account that submitted the buyer's review on the buyer's behalf, if any
+    pub delegate: Option<usize>,
+    /// Weight of a delegated review relative to the buyer's own (0..1)
+    pub delegate_attenuation: f64,
+    /// Rating the producer gives the buyer
+    pub producer_rating: f64,
+    /// Unix timestamp in seconds
+    pub timestamp: u64,
+}
+
+/// Append-only log of transactions
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TransactionLog {
+    genesis: Marketplace,
+    /// Compacted state of all transactions before compacted_until
+    base: Option<Marketplace>,
+    compacted_until: u64,
+    transactions: Vec<Transaction>,
+    /// Hash after the last compacted transaction
+    base_hash: [u8; 32],
+    head_hash: [u8; 32],
+    /// 0 = no checkpoints
+    checkpoint_interval: usize,
+    /// (i, state after transactions[..i]), ordered by i
+    checkpoints: Vec<(usize, Marketplace)>,
+}
+
+/// How long full transaction history is kept before compaction
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RetentionPolicy {
+    /// Transactions older than now - keep_seconds are compacted
+    pub keep_seconds: u64,
+}
+
+impl TransactionLog {
+    /// Create an empty log for a marketplace starting with `genesis_users` users
+    pub fn new(genesis_users: usize) -> Self {
+        Self::from_genesis(Marketplace::new(genesis_users))
+    }
+
+    /// Create an empty log replayed on top of `genesis` (e.g. to keep its edge weighting)
+    pub fn from_genesis(genesis: Marketplace) -> Self {
+        TransactionLog {
+            genesis,
+            base: None,
+            compacted_until: 0,
+            transactions: Vec::new(),
+            base_hash: [0; 32],
+            head_hash: [0; 32],
+            checkpoint_interval: DEFAULT_CHECKPOINT_INTERVAL,
+            checkpoints: Vec::new(),
+        }
+    }
+
+    /// Append a transaction
+    ///
+    /// Returns: Err if buyer and producer are the same user, the producer is the
+    /// buyer's delegate, or the timestamp is older than the last logged or compacted
+    /// transaction
+    pub fn append(&mut self, transaction: Transaction) -> Result<(), String> {
+        if transaction.buyer == transaction.producer {
+            return Err(format!(
+                "user {} cannot transact with itself",
+                transaction.buyer
+            ));
+        }
+        if transaction.delegate == Some(transaction.producer) {
+            return Err(format!(
+                "producer {} cannot review itself as the buyer's delegate",
+                transaction.producer
+            ));
+        }
+        if transaction.timestamp < self.compacted_until {
+            return Err(format!(
+                "timestamp {} is before the compaction point {}",
+                transaction.timestamp, self.compacted_until
+            ));
+        }
+        if let Some(last) = self.transactions.last() {
+            if transaction.timestamp < last.timestamp {
+                return Err(format!(
+                    "timestamp {} is older than last logged timestamp {}",
+                    transaction.timestamp, last.timestamp
+                ));
+            }
+        }
+        self.head_hash = chain(self.head_hash, &[transaction]);
+        self.transactions.push(transaction);
+
+        let last = self.checkpoints.last().map_or(0, |(i, _)| *i);
+        let len = self.transactions.len();
+        if self.checkpoint_interval > 0 && len - last >= self.checkpoint_interval {
+            let state = self.replay_prefix(len);
+            self.checkpoints.push((len, state));
+        }
+        Ok(())
+    }
+
+    /// Keep a checkpoint every `interval` transactions (0 = none), rebuilding existing ones
+    pub fn set_checkpoint_interval(&mut self, interval: usize) {
+        self.checkpoint_interval = interval;
+        self.rebuild_checkpoints();
+    }
+
+    fn rebuild_checkpoints(&mut self) {
+        self.checkpoints.clear();
+        if self.checkpoint_interval == 0 {
+            return;
+        }
+
+        let mut state = self.start_state();
+        let mut end = 0;
+        for chunk in self.transactions.chunks_exact(self.checkpoint_interval) {
+            state = replay_onto(state, chunk);
+            end += chunk.len();
+            self.checkpoints.push((end, state.clone()));
+        }
+    }
+
+    /// Hash committing every transaction appended so far (including compacted ones)
+    pub fn head_hash(&self) -> [u8; 32] {
+        self.head_hash
+    }
+
+    /// All logged transactions, oldest first
+    pub fn transactions(&self) -> &[Transaction] {
+        &self.transactions
+    }
+
+    /// Uncompacted transactions with t0 ≤ timestamp < t1, oldest first, without copying
+    pub fn iter_range(&self, range: Range<u64>) -> impl Iterator<Item = &Transaction> + '_ {
+        let start = self
+            .transactions
+            .partition_point(|tx| tx.timestamp < range.start);
+        let end = self
+            .transactions
+            .partition_point(|tx| tx.timestamp < range.end)
+            .max(start);
+        self.transactions[start..end].iter()
+    }
+
+    /// Rebuild the marketplace by replaying every transaction from genesis
+    /// (or from the compacted base state)
+    ///
+    /// Users referenced beyond the genesis user count are added as they appear.
+    pub fn replay(&self) -> Marketplace {
+        self.replay_prefix(self.transactions.len())
+    }
+
+    /// Rebuild a marketplace from only the transactions tagged with `region`
+    ///
+    /// Its graph_values are region-restricted scores: EC, W and reputations only
+    /// see in-region transactions, so fee levels of other regions do not mix in.
+    ///
+    /// Returns: Err if history was compacted (the base state mixes all regions)
+    pub fn replay_region(&self, region: u32) -> Result<Marketplace, String> {
+        if self.base.is_some() {
+            return Err("compacted history cannot be split by region".to_string());
+        }
+
+        let regional: Vec<Transaction> = self
+            .transactions
+            .iter()
+            .copied()
+            .filter(|tx| tx.region == Some(region))
+            .collect();
+        Ok(replay_onto(self.genesis.clone(), &regional))
+    }
+
+    /// Rebuild the marketplace with different edge weighting, e.g. to backtest it
+    ///
+    /// Uncompacted transactions are replayed under `weighting`; the compacted base
+    /// only has its edges re-aggregated, since its per-transaction weights are final.
+    pub fn replay_with(&self, weighting: EdgeWeighting) -> Marketplace {
+        let mut start = self.start_state();
+        start.set_weighting(weighting);
+        replay_onto(start, &self.transactions)
+    }
+
+    /// Rebuild the marketplace as it was after every transaction with timestamp ≤ `timestamp`
+    ///
+    /// Starts from the nearest checkpoint (or the compacted base state) at or before
+    /// `timestamp` and replays only the transactions after it.
+    ///
+    /// Returns: Err if `timestamp` is before the compaction point (that history is gone)
+    pub fn replay_until(&self, timestamp: u64) -> Result<Marketplace, String> {
+        if timestamp < self.compacted_until {
+            return Err(format!(
+                "timestamp {} is before the compaction point {}",
+                timestamp, self.compacted_until
+            ));
+        }
+
+        let end = self
+            .transactions
+            .partition_point(|tx| tx.timestamp <= timestamp);
+        Ok(self.replay_prefix(end))
+    }
+
+    /// Find the transaction after which a score first deviates from its expected value
+    ///
+    /// Binary search over replay prefixes for the first one with
+    /// |score(state) - expected| > threshold, e.g. with
+    /// score = |m| m.reputation(user) or |m| m.graph_value(user).
+    /// Assumes the deviation persists once it appears (O(log n) replays). Early
+    /// prefixes may not contain the user yet (m.user_count() ≤ user).
+    ///
+    /// Returns: index into transactions(), or None if the current state does not
+    /// deviate or the compacted base state already does
+    pub fn first_deviation(
+        &self,
+        score: impl Fn(&Marketplace) -> f64,
+        expected: f64,
+        threshold: f64,
+    ) -> Option<usize> {
+        let deviates = |end: usize| (score(&self.replay_prefix(end)) - expected).abs() > threshold;
+        if deviates(0) || !deviates(self.transactions.len()) {
+            return None;
+        }
+
+        // Invariant: prefix lo does not deviate, prefix hi does
+        let (mut lo, mut hi) = (0, self.transactions.len());
+        while hi - lo > 1 {
+            let mid = (lo + hi) / 2;
+            if deviates(mid) {
+                hi = mid;
+            } else {
+                lo = mid;
+            }
+        }
+        Some(hi - 1)
+    }
+
+    /// State after transactions[..end], replayed from the nearest checkpoint
+    fn replay_prefix(&self, end: usize) -> Marketplace {
+        let k = self.checkpoints.partition_point(|(i, _)| *i <= end);
+        match k.checked_sub(1).map(|k| &self.checkpoints[k]) {
+            Some((i, state)) => replay_onto(state.clone(), &self.transactions[*i..end]),
+            None => replay_onto(self.start_state(), &self.transactions[..end]),
+        }
+    }
+
+    /// Compacted base state, or genesis if nothing was compacted
+    fn start_state(&self) -> Marketplace {
+        match &self.base {
+            Some(base) => base.clone(),
+            None => self.genesis.clone(),
+        }
+    }
+
+    /// Compact all transactions with timestamp < before into the base state
+    pub fn compact(&mut self, before: u64) {
+        if before <= self.compacted_until {
+            return;
+        }
+
+        let split = self
+            .transactions
+            .partition_point(|tx| tx.timestamp < before);
+        self.base = Some(self.replay_prefix(split));
+        self.base_hash = chain(self.base_hash, &self.transactions[..split]);
+        self.transactions.drain(..split);
+        self.compacted_until = before;
+
+        // Checkpoints inside the compacted prefix are covered by the base
+        self.checkpoints.retain(|(i, _)| *i > split);
+        for (i, _) in self.checkpoints.iter_mut() {
+            *i -= split;
+        }
+    }
+
+    /// Delete a user's transactions and rebuild the marketplace by replay
+    ///
+    /// Neighbors' edge weights, EC and reputations are recomputed as if the user's
+    /// uncompacted transactions had never happened. Residual effects that remain:
+    /// - the user keeps its index (users are not renumbered)
+    /// - transactions compacted into the base state cannot be separated from it:
+    ///   their edge weights, the reviews they produced (for and by the user) and
+    ///   their tx counts are kept; a user with no compacted transactions ends with
+    ///   no edges, reputation R_MIN and 0 transactions
+    ///
+    /// The head hash is re-chained from the compaction point, so it then commits
+    /// the edited history.
+    pub fn forget(&mut self, user: usize) -> Marketplace {
+        self.transactions
+            .retain(|tx| tx.buyer != user && tx.producer != user);
+        self.head_hash = chain(self.base_hash, &self.transactions);
+        self.rebuild_checkpoints();
+
+        let mut marketplace = self.replay();
+        while marketplace.user_count() <= user {
+            marketplace.add_user();
+        }
+        marketplace
+    }
+
+    /// Compact everything outside the retention window ending at `now`
+    pub fn apply_retention(&mut self, now: u64, policy: RetentionPolicy) {
+        self.compact(now.saturating_sub(policy.keep_seconds));
+    }
+
+    /// Timestamp before which history has been compacted (0 if never compacted)
+    pub fn compacted_until(&self) -> u64 {
+        self.compacted_until
+    }
+}
+
+/// Apply transactions in order on top of a marketplace state
+fn replay_onto(mut marketplace: Marketplace, transactions: &[Transaction]) -> Marketplace {
+    for tx in transactions {
+        while marketplace.user_count() <= tx.buyer.max(tx.producer) {
+            marketplace.add_user();
+        }
+        // append rejects self-transactions, so recording cannot fail
+        marketplace
+            .record(tx)
+            .expect("logged transactions are valid");
+    }
+    marketplace
+}
+
+/// Extend a hash chain: h = SHA-256(h || tx) for each transaction in order
+fn chain(mut hash: [u8; 32], transactions: &[Transaction]) -> [u8; 32] {
+    for tx in transactions {
+        let mut input = hash.to_vec();
+        input.extend(transaction_bytes(tx));
+        hash = sha256(&input);
+    }
+    hash
+}
+
+/// Fixed little-endian encoding of a transaction for hashing
+fn transaction_bytes(tx: &Transaction) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    bytes.extend((tx.buyer as u64).to_le_bytes());
+    bytes.extend((tx.producer as u64).to_le_bytes());
+    bytes.extend(tx.fee.to_le_bytes());
+    match tx.duration_hours {
+        Some(hours) => {
+            bytes.push(1);
+            bytes.extend(hours.to_le_bytes());
+        }
+        None => bytes.push(0),
+    }
+    match tx.region {
+        Some(region) => {
+            bytes.push(1);
+            bytes.extend(region.to_le_bytes());
+        }
+        None => bytes.push(0),
+    }
+    bytes.extend(tx.buyer_rating.to_le_bytes());
+    match tx.delegate {
+        Some(delegate) => {
+            bytes.push(1);
+            bytes.extend((delegate as u64).to_le_bytes());
+            bytes.extend(tx.delegate_attenuation.to_le_bytes());
+        }
+        None => bytes.push(0),
+    }
+    bytes.extend(tx.producer_rating.to_le_bytes());
+    bytes.extend(tx.timestamp.to_le_bytes());
+    bytes
+}
+
+/// SHA-256 (FIPS 180-4)
+pub(crate) fn sha256(data: &[u8]) -> [u8; 32] {
+    const K: [u32; 64] = [
+        0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4,
+        0xab1c5ed5, 0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe,
+        0x9bdc06a7, 0xc19bf174, 0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f,
+        0x4a7484aa, 0x5cb0a9dc, 0x76f988da, 0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7,
+        0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967, 0x27b70a85, 0x2e1b2138, 0x4d2c6dfc,
+        0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85, 0xa2bfe8a1, 0xa81a664b,
+        0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070, 0x19a4c116,
+        0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+        0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7,
+        0xc67178f2,
+    ];
+    let mut h: [u32; 8] = [
+        0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab,
+        0x5be0cd19,
+    ];
+
+    // Pad: 0x80, zeros, then the bit length as a big-endian u64
+    let mut message = data.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend(((data.len() as u64) * 8).to_be_bytes());
+
+    for chunk in message.chunks(64) {
+        let mut w = [0u32; 64];
+        for (i, word) in chunk.chunks(4).enumerate() {
+            w[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+        for i in 16..64 {
+            let s0 = w[i - 15].rotate_right(7) ^ w[i - 15].rotate_right(18) ^ (w[i - 15] >> 3);
+            let s1 = w[i - 2].rotate_right(17) ^ w[i - 2].rotate_right(19) ^ (w[i - 2] >> 10);
+            w[i] = w[i - 16]
+                .wrapping_add(s0)
+                .wrapping_add(w[i - 7])
+                .wrapping_add(s1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut hh] = h;
+        for i in 0..64 {
+            let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+            let ch = (e & f) ^ (!e & g);
+            let t1 = hh
+                .wrapping_add(s1)
+                .wrapping_add(ch)
+                .wrapping_add(K[i])
+                .wrapping_add(w[i]);
+            let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+            let maj = (a & b) ^ (a & c) ^ (b & c);
+            let t2 = s0.wrapping_add(maj);
+            hh = g;
+            g = f;
+            f = e;
+            e = d.wrapping_add(t1);
+            d = c;
+            c = b;
+            b = a;
+            a = t1.wrapping_add(t2);
+        }
+        for (state, v) in h.iter_mut().zip([a, b, c, d, e, f, g, hh]) {
+            *state = state.wrapping_add(v);
+        }
+    }
+
+    let mut digest = [0u8; 32];
+    for (out, word) in digest.chunks_mut(4).zip(h) {
+        out.copy_from_slice(&word.to_be_bytes());
+    }
+    digest
+}
@@ -0,0 +1,40 @@
+//! Visualization
+//!
+//! Exports a user's ego network (see graph::sample_neighborhood) as Graphviz DOT,
+//! labelling nodes with their score and edges with their weight:
+//!   dot -Tpng ego.dot -o ego.png
+
+use crate::graph;
+use std::fmt::Write;
+
+/// Render the ego network of `user` up to `depth` hops as a DOT graph
+///
+/// Input: scores = per-node score shown in the label (e.g. graph value)
+pub fn ego_dot<const N: usize>(
+    weights: &[[f64; N]; N],
+    scores: &[f64; N],
+    user: usize,
+    depth: usize,
+    max_nodes: usize,
+) -> String {
+    let nodes = graph::sample_neighborhood(weights, user, depth, max_nodes);
+
+    let mut dot = String::from("graph ego {\n");
+    for &u in &nodes {
+        let style = if u == user { ", style=bold" } else { "" };
+        let _ = writeln!(
+            dot,
+            "    {} [label=\"{}\\n{:.6}\"{}];",
+            u, u, scores[u], style
+        );
+    }
+    for (i, &u) in nodes.iter().enumerate() {
+        for &v in &nodes[i + 1..] {
+            if weights[u][v] > 0.0 {
+                let _ = writeln!(dot, "    {} -- {} [label=\"{}\"];", u, v, weights[u][v]);
+            }
+        }
+    }
+    dot.push_str("}\n");
+    dot
+}